@@ -1,72 +1,198 @@
 use clap::{arg, Command};
-use hyprland::event_listener::{EventListener,WindowEventData};
+use hyprland::data::{Client, Clients, FullscreenMode};
+use hyprland::event_listener::{EventListener, WindowEventData};
+use hyprland::prelude::*;
+use i3_prop::bar::{self, Window};
+use i3_prop::display::{Display, Render};
+use i3_prop::printer;
+use i3_prop::select::{self, Candidate, Selector};
+use reconnect::Backoff;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
+use std::{cell::RefCell, rc::Rc};
 
-#[derive(Serialize, Deserialize)]
+/// Active window properties, all `None` when no window is focused.
+#[derive(Clone, Default, Serialize, Deserialize)]
 struct Props {
     title: Option<String>,
     class: Option<String>,
     address: Option<String>,
+    fullscreen: bool,
+}
+
+impl Props {
+    fn from_client(client: &Client) -> Self {
+        Props {
+            title: Some(client.title.clone()),
+            class: Some(client.class.clone()),
+            address: Some(client.address.to_string()),
+            fullscreen: client.fullscreen != FullscreenMode::None,
+        }
+    }
+
+    fn from_event(data: &WindowEventData, fullscreen: bool) -> Self {
+        Props {
+            title: Some(data.title.clone()),
+            class: Some(data.class.clone()),
+            address: Some(data.address.to_string()),
+            fullscreen,
+        }
+    }
 }
 
 fn cli() -> Command {
     Command::new("hypr-prop")
-        .about("Window properties change listen tool for hyprland")
+        .about("Window properties query tool for hyprland")
+        .group(bar::group())
         .args(vec![
+            arg!(-l --"listen" "Listen for focus, title and fullscreen changes"),
             arg!(-a --"address" "Prints window address"),
             arg!(-c --"class" "Prints window class"),
             arg!(-t --"title" "Print window title"),
         ])
+        .args(bar::args())
+        .args(printer::args())
+        .args(select::args())
+        .arg(select::app_id_arg())
+        .arg(select::pid_arg())
 }
 
 fn main() -> hyprland::Result<()> {
     let matches = cli().get_matches();
 
-    let print_address = matches.get_flag("address");
-    let print_class = matches.get_flag("class");
-    let print_title = matches.get_flag("title");
+    let mut display = Display::from_matches(&matches, &["title", "class", "address"], Hypr);
+    display.header();
+
+    if let Some(selector) = Selector::from_matches(&matches) {
+        let windows = Clients::get()?
+            .iter()
+            .filter(|client| selector.matches(&candidate(client)))
+            .map(Props::from_client)
+            .collect::<Vec<Props>>();
+        let windows = windows.iter().collect::<Vec<&Props>>();
 
-    let print_props = !print_title && !print_class && !print_address;
+        match (selector.is_single(), windows.first()) {
+            (true, Some(props)) => display.window(props),
+            (true, None) => {
+                eprintln!("No window matches given id");
+                std::process::exit(1);
+            }
+            (false, _) => display.windows(&windows),
+        }
+        return Ok(());
+    }
+
+    let props = get_active_props();
+    display.window(&props);
+
+    if !matches.get_flag("listen") {
+        return Ok(());
+    }
+
+    let active = Rc::new(RefCell::new(props));
+    let display = Rc::new(RefCell::new(display));
+    let backoff = Rc::new(RefCell::new(Backoff::new()));
+
+    loop {
+        let error = match listener(&active, &display, &backoff).start_listener() {
+            Ok(()) => "connection closed".to_string(),
+            Err(e) => e.to_string(),
+        };
+        eprintln!("Event listener error: {error}");
+        display.borrow_mut().disconnected(&error);
+
+        let props = backoff
+            .borrow_mut()
+            .retry(Client::get_active)
+            .map_or_else(Props::default, |client| Props::from_client(&client));
+        display.borrow_mut().window(&props);
+        *active.borrow_mut() = props;
+    }
+}
 
+/// Listener updating the shared active window and printing it on changes.
+/// Every event resets the backoff, the connection works.
+fn listener(
+    active: &Rc<RefCell<Props>>,
+    display: &Rc<RefCell<Display<Hypr>>>,
+    backoff: &Rc<RefCell<Backoff>>,
+) -> EventListener {
     let mut listener = EventListener::new();
+
+    let (state, out, delivered) = (active.clone(), display.clone(), backoff.clone());
     listener.add_active_window_changed_handler(move |data| {
-        let props = data.clone().unwrap();
+        delivered.borrow_mut().reset();
+        let props = match data {
+            Some(data) => Props::from_event(&data, get_active_props().fullscreen),
+            None => Props::default(),
+        };
+        out.borrow_mut().window(&props);
+        *state.borrow_mut() = props;
+    });
 
-        if print_props {
-            display_props(&props);
-        }
-        if print_title {
-            display_title(&props.title);
-        }
-        if print_class {
-            display_class(&props.class);
-        }
-        if print_address {
-            display_address(&props.address.to_string());
+    let (state, out, delivered) = (active.clone(), display.clone(), backoff.clone());
+    listener.add_window_title_changed_handler(move |data| {
+        delivered.borrow_mut().reset();
+        let mut props = state.borrow_mut();
+        if props.address == Some(data.address.to_string()) {
+            props.title = Some(data.title);
+            out.borrow_mut().window(&props);
         }
     });
-    listener.start_listener()?;
-    Ok(())
-}
 
-fn display_props(data: &WindowEventData) {
-    let props = Props {
-        title: Some(data.title.clone()),
-        class: Some(data.class.clone()),
-        address: Some(data.address.to_string())
-    };
-    println!("{}", json!(props));
+    let (state, out, delivered) = (active.clone(), display.clone(), backoff.clone());
+    listener.add_fullscreen_state_changed_handler(move |fullscreen| {
+        delivered.borrow_mut().reset();
+        let mut props = state.borrow_mut();
+        props.fullscreen = fullscreen;
+        out.borrow_mut().window(&props);
+    });
+
+    listener
 }
 
-fn display_title(title: &str) {
-    println!("{}", title);
+fn get_active_props() -> Props {
+    Client::get_active()
+        .ok()
+        .flatten()
+        .map_or_else(Props::default, |client| Props::from_client(&client))
 }
 
-fn display_class(class: &str) {
-    println!("{}", class);
+/// Hyprland clients as printed by the tool.
+struct Hypr;
+
+impl Render for Hypr {
+    type Window = Props;
+
+    fn bar_window<'a>(&self, props: &'a Props) -> Window<'a> {
+        Window {
+            app: props.class.as_deref(),
+            title: props.title.as_deref(),
+        }
+    }
+
+    fn props(&self, props: &Props) -> Value {
+        json!(props)
+    }
+
+    fn field(&self, props: &Props, flag: &str) -> String {
+        let field = match flag {
+            "title" => &props.title,
+            "class" => &props.class,
+            _ => &props.address,
+        };
+        field.clone().unwrap_or_default()
+    }
 }
 
-fn display_address(address: &str) {
-    println!("{}", address);
+fn candidate(client: &Client) -> Candidate<'_> {
+    Candidate {
+        id: client.address.to_string(),
+        pid: Some(client.pid),
+        apps: vec![client.class.as_str(), client.initial_class.as_str()],
+        workspaces: vec![
+            client.workspace.name.clone(),
+            client.workspace.id.to_string(),
+        ],
+    }
 }
@@ -0,0 +1,127 @@
+use clap::Command;
+use hyprland::data::{Monitors, Workspace as HyprWorkspace, Workspaces};
+use hyprland::dispatch::{
+    Dispatch, DispatchType, MonitorIdentifier, WorkspaceIdentifier, WorkspaceIdentifierWithSpecial,
+};
+use hyprland::event_listener::EventListener;
+use hyprland::prelude::*;
+use std::sync::mpsc;
+use sway_workspace::{Compositor, FocusEvents, Options, Target, Workspace};
+
+fn cli() -> Command {
+    Command::new("hypr-workspace")
+        .about("Manage workspaces in hyprland")
+        .args(sway_workspace::args())
+}
+
+struct Hypr {
+    dry_run: bool,
+}
+
+impl Hypr {
+    fn dispatch(&self, dispatch: DispatchType) -> hyprland::Result<()> {
+        if self.dry_run {
+            println!("{dispatch:?}");
+            return Ok(());
+        }
+        Dispatch::call(dispatch)
+    }
+}
+
+impl Compositor for Hypr {
+    type Error = hyprland::error::HyprError;
+
+    fn workspaces(&mut self) -> hyprland::Result<Vec<Workspace>> {
+        let active = HyprWorkspace::get_active()?.id;
+        let visible: Vec<i32> = Monitors::get()?
+            .into_iter()
+            .map(|monitor| monitor.active_workspace.id)
+            .collect();
+        let mut workspaces: Vec<Workspace> = Workspaces::get()?
+            .into_iter()
+            // Special workspaces are scratchpads, not something to cycle through.
+            .filter(|ws| !ws.name.starts_with("special:"))
+            .map(|ws| Workspace {
+                id: ws.id as i64,
+                num: ws.name.parse().ok(),
+                name: ws.name,
+                output: ws.monitor,
+                focused: ws.id == active,
+                visible: visible.contains(&ws.id),
+                empty: ws.windows == 0,
+            })
+            .collect();
+        sway_workspace::sort_by_num(&mut workspaces);
+        Ok(workspaces)
+    }
+
+    fn focus(&mut self, target: &Target) -> hyprland::Result<()> {
+        self.dispatch(DispatchType::Workspace(workspace_id(target)))
+    }
+
+    fn move_window(&mut self, target: &Target) -> hyprland::Result<()> {
+        self.dispatch(DispatchType::MoveToWorkspaceSilent(
+            workspace_id(target),
+            None,
+        ))
+    }
+
+    /// Hyprland numbers are workspace ids, they can not be changed.
+    fn rename(&mut self, ws: &Workspace, _name: &str) -> hyprland::Result<()> {
+        Err(hyprland::error::HyprError::Other(format!(
+            "Can not renumber workspace {}, hyprland workspace numbers are fixed ids",
+            ws.name
+        )))
+    }
+
+    fn outputs(&mut self) -> hyprland::Result<Vec<String>> {
+        let mut monitors = Monitors::get()?.to_vec();
+        monitors.retain(|monitor| !monitor.disabled);
+        monitors.sort_by_key(|monitor| (monitor.x, monitor.y));
+        Ok(monitors.into_iter().map(|monitor| monitor.name).collect())
+    }
+
+    fn move_workspace(&mut self, ws: &Workspace, output: &str) -> hyprland::Result<()> {
+        self.dispatch(DispatchType::MoveWorkspaceToMonitor(
+            WorkspaceIdentifier::Id(ws.id as i32),
+            MonitorIdentifier::Name(output),
+        ))
+    }
+
+    /// The listener only takes callbacks, so it runs in its own thread.
+    fn focus_events(&mut self) -> hyprland::Result<FocusEvents<hyprland::error::HyprError>> {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut listener = EventListener::new();
+            let workspace_sender = sender.clone();
+            listener.add_workspace_changed_handler(move |_| {
+                let _ = workspace_sender.send(Ok(()));
+            });
+            let monitor_sender = sender.clone();
+            listener.add_active_monitor_changed_handler(move |_| {
+                let _ = monitor_sender.send(Ok(()));
+            });
+            if let Err(e) = listener.start_listener() {
+                let _ = sender.send(Err(e));
+            }
+        });
+        Ok(Box::new(receiver.into_iter()))
+    }
+}
+
+fn workspace_id(target: &Target) -> WorkspaceIdentifierWithSpecial<'static> {
+    match target {
+        Target::Workspace(ws) => WorkspaceIdentifierWithSpecial::Id(ws.id as i32),
+        Target::New(num) => WorkspaceIdentifierWithSpecial::Id(*num),
+    }
+}
+
+fn main() {
+    let matches = cli().get_matches();
+
+    let options = Options::from_matches(&matches);
+    let mut hypr = Hypr {
+        dry_run: options.dry_run,
+    };
+    sway_workspace::report(sway_workspace::run(&mut hypr, &options), &options);
+}
@@ -36,3 +36,41 @@ impl std::fmt::Display for Direction {
             .fmt(f)
     }
 }
+
+/// What to do when the focus leaves a floating window through the window
+/// manager, as directional focus from floating windows only cycles through
+/// other floating windows.
+#[derive(Clone)]
+pub enum FloatingPolicy {
+    /// Plain `focus <direction>`.
+    Direction,
+    /// `focus mode_toggle`, jumps back to the tiled windows.
+    ModeToggle,
+    /// Leave the focus where it is.
+    Stay,
+}
+
+impl FloatingPolicy {
+    /// Window manager command for focus in given direction, if any.
+    pub fn command(&self, direction: &Direction) -> Option<String> {
+        match self {
+            Self::Direction => Some(format!("focus {}", direction)),
+            Self::ModeToggle => Some("focus mode_toggle".to_string()),
+            Self::Stay => None,
+        }
+    }
+}
+
+impl ValueEnum for FloatingPolicy {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Direction, Self::ModeToggle, Self::Stay]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Direction => PossibleValue::new("direction"),
+            Self::ModeToggle => PossibleValue::new("mode-toggle"),
+            Self::Stay => PossibleValue::new("stay"),
+        })
+    }
+}
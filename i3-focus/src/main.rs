@@ -2,7 +2,7 @@ use clap::{arg, value_parser, Command};
 use i3_focus::{
     nvim, tmux,
     wezterm::{self, WezTermId},
-    zellij, Direction,
+    zellij, Direction, FloatingPolicy,
 };
 use i3_ipc::{Connect, I3Stream, I3};
 use i3ipc_types::reply;
@@ -14,6 +14,9 @@ fn cli() -> Command {
         .args(vec![
             arg!(<DIRECTION> "Focus direction").value_parser(value_parser!(Direction)),
             arg!(--"skip-nvim" "Skip nvim check"),
+            arg!(--"floating" <POLICY> "How to leave a floating window")
+                .value_parser(value_parser!(FloatingPolicy))
+                .default_value("direction"),
         ])
 }
 
@@ -26,7 +29,16 @@ fn main() -> io::Result<()> {
         .get_one::<Direction>("DIRECTION")
         .expect("Direction has to be provided");
 
-    match get_focused_name(&mut i3) {
+    let tree = i3.get_tree().ok();
+    let policy = if tree.as_ref().is_some_and(is_focused_floating) {
+        matches
+            .get_one::<FloatingPolicy>("floating")
+            .expect("Floating policy has a default")
+    } else {
+        &FloatingPolicy::Direction
+    };
+
+    match tree.as_ref().and_then(get_focused_name) {
         Some(name) => {
             let skip_vim = matches.get_flag("skip-nvim");
             let nvim_id = get_nvim_id(&name);
@@ -56,17 +68,18 @@ fn main() -> io::Result<()> {
                 }
             }
 
-            handle_i3(&mut i3, direction);
+            handle_i3(&mut i3, direction, policy);
         }
-        None => handle_i3(&mut i3, direction),
+        None => handle_i3(&mut i3, direction, policy),
     }
 
     Ok(())
 }
 
-fn handle_i3(i3: &mut I3Stream, direction: &Direction) {
-    i3.run_command(format!("focus {}", direction))
-        .unwrap_or_default();
+fn handle_i3(i3: &mut I3Stream, direction: &Direction, policy: &FloatingPolicy) {
+    if let Some(command) = policy.command(direction) {
+        i3.run_command(command).unwrap_or_default();
+    }
 }
 
 fn handle_nvim(id: usize, direction: &Direction) {
@@ -85,17 +98,12 @@ fn handle_zellij(id: &str, direction: &Direction) -> bool {
     zellij::focus(id, direction)
 }
 
-fn get_focused_name(i3: &mut I3Stream) -> Option<String> {
-    match i3.get_tree() {
-        Ok(tree) => {
-            let focused_list = get_focused(&tree);
+fn get_focused_name(tree: &reply::Node) -> Option<String> {
+    let focused_list = get_focused(tree);
 
-            match focused_list.first() {
-                Some(focused) => focused.name.clone(),
-                None => None,
-            }
-        }
-        Err(_) => None,
+    match focused_list.first() {
+        Some(focused) => focused.name.clone(),
+        None => None,
     }
 }
 
@@ -150,9 +158,19 @@ fn collect_focused<'a>(node: &'a reply::Node, mut r: Vec<&'a reply::Node>) -> Ve
     for n in &node.nodes {
         r = collect_focused(n, r)
     }
+    for n in &node.floating_nodes {
+        r = collect_focused(n, r)
+    }
     r
 }
 
+fn is_focused_floating(node: &reply::Node) -> bool {
+    node.floating_nodes
+        .iter()
+        .any(|n| !get_focused(n).is_empty())
+        || node.nodes.iter().any(is_focused_floating)
+}
+
 fn get_focused(node: &reply::Node) -> Vec<&reply::Node> {
     let v: Vec<&reply::Node> = vec![];
     collect_focused(node, v)
@@ -16,7 +16,11 @@ impl Direction {
 
 pub fn focus(id: usize, direction: &Direction) {
     if let Err(_) = switch_window(id, direction) {
-        cmd(&current_exe(), &["--skip-nvim", &direction.to_string()]);
+        // Run again with the same arguments, just skipping nvim this time.
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let mut args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+        args.push("--skip-nvim");
+        cmd(&current_exe(), &args);
     };
 }
 
@@ -0,0 +1,212 @@
+use clap::{arg, Arg, ArgGroup, ArgMatches};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Status bar the window is rendered for.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Waybar,
+    I3bar,
+    Polybar,
+}
+
+impl Format {
+    pub fn from_matches(matches: &ArgMatches) -> Option<Format> {
+        if matches.get_flag("waybar") {
+            Some(Format::Waybar)
+        } else if matches.get_flag("i3bar") {
+            Some(Format::I3bar)
+        } else if matches.get_flag("polybar") {
+            Some(Format::Polybar)
+        } else {
+            None
+        }
+    }
+}
+
+/// Icon mapping, read from `$XDG_CONFIG_HOME/i-use-rust-btw/prop.toml`:
+///
+/// ```toml
+/// default-icon = ""
+///
+/// [icons]
+/// firefox = ""
+/// "org.wezfurlong.wezterm" = ""
+/// ```
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    #[serde(default)]
+    pub default_icon: String,
+    #[serde(default)]
+    pub icons: HashMap<String, String>,
+}
+
+impl Config {
+    /// Load config from `path` or the default location. Missing file means
+    /// no icons, broken one is reported and ends the process.
+    pub fn load(path: Option<&str>) -> Config {
+        let path = match path {
+            Some(path) => path.to_string(),
+            None => default_config_path(),
+        };
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return Config::default(),
+        };
+        toml::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("Can not parse config {path}: {e}");
+            std::process::exit(1);
+        })
+    }
+
+    /// Icon for app id / class, matched case insensitive.
+    pub fn icon(&self, app: Option<&str>) -> &str {
+        app.and_then(|app| {
+            self.icons
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(app))
+                .map(|(_, icon)| icon.as_str())
+        })
+        .unwrap_or(&self.default_icon)
+    }
+}
+
+fn default_config_path() -> String {
+    let dir = std::env::var("XDG_CONFIG_HOME").unwrap_or_else(|_| {
+        let home = std::env::var("HOME").unwrap_or("/tmp".into());
+        format!("{home}/.config")
+    });
+    format!("{dir}/i-use-rust-btw/prop.toml")
+}
+
+/// What a bar needs to know about a window, `None` when nothing is focused.
+pub struct Window<'a> {
+    pub app: Option<&'a str>,
+    pub title: Option<&'a str>,
+}
+
+/// Renders windows in the format expected by the selected bar.
+pub struct Bar {
+    format: Format,
+    config: Config,
+}
+
+impl Bar {
+    pub fn new(format: Format, config: Config) -> Self {
+        Self { format, config }
+    }
+
+    /// Lines that have to be printed once before any window.
+    pub fn header(&self) -> Option<String> {
+        match self.format {
+            Format::I3bar => Some(format!("{}\n[", json!({ "version": 1 }))),
+            _ => None,
+        }
+    }
+
+    pub fn render(&self, window: &Window) -> String {
+        let app = window.app.unwrap_or_default();
+        let title = window.title.unwrap_or_default();
+        let icon = self.config.icon(window.app);
+        let text = format!("{icon} {title}").trim().to_string();
+        let tooltip = [app, title]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<&str>>()
+            .join(": ");
+
+        match self.format {
+            Format::Waybar => json!({
+                "text": text,
+                "tooltip": tooltip,
+                "class": if window.app.is_some() { app } else { "empty" },
+                "alt": app,
+            })
+            .to_string(),
+            // Status lines are elements of an endless array.
+            Format::I3bar => format!(
+                "{},",
+                json!([{
+                    "name": "window",
+                    "instance": app,
+                    "full_text": text,
+                }])
+            ),
+            Format::Polybar => text.replace('%', "%%"),
+        }
+    }
+}
+
+pub fn args() -> Vec<Arg> {
+    vec![
+        arg!(--"waybar" "Print JSON for waybar custom module"),
+        arg!(--"i3bar" "Print i3bar protocol"),
+        arg!(--"polybar" "Print text for polybar script module"),
+        arg!(--"config" <FILE> "Config file with app icons for bar output"),
+    ]
+}
+
+pub fn group() -> ArgGroup {
+    ArgGroup::new("bar").args(["waybar", "i3bar", "polybar"])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config() -> Config {
+        Config {
+            default_icon: "?".to_string(),
+            icons: HashMap::from([("firefox".to_string(), "F".to_string())]),
+        }
+    }
+
+    #[test]
+    fn test_icon_is_matched_case_insensitive() {
+        assert_eq!(config().icon(Some("Firefox")), "F");
+    }
+
+    #[test]
+    fn test_default_icon_for_unknown_app() {
+        assert_eq!(config().icon(Some("foot")), "?");
+        assert_eq!(config().icon(None), "?");
+    }
+
+    #[test]
+    fn test_waybar_output() {
+        let bar = Bar::new(Format::Waybar, config());
+        let line = bar.render(&Window {
+            app: Some("firefox"),
+            title: Some("Rust"),
+        });
+
+        assert_eq!(
+            line,
+            r#"{"alt":"firefox","class":"firefox","text":"F Rust","tooltip":"firefox: Rust"}"#
+        );
+    }
+
+    #[test]
+    fn test_waybar_output_without_window() {
+        let bar = Bar::new(Format::Waybar, Config::default());
+        let line = bar.render(&Window {
+            app: None,
+            title: None,
+        });
+
+        assert_eq!(line, r#"{"alt":"","class":"empty","text":"","tooltip":""}"#);
+    }
+
+    #[test]
+    fn test_polybar_output_escapes_percent() {
+        let bar = Bar::new(Format::Polybar, config());
+        let line = bar.render(&Window {
+            app: Some("firefox"),
+            title: Some("100% Rust"),
+        });
+
+        assert_eq!(line, "F 100%% Rust");
+    }
+}
@@ -0,0 +1,227 @@
+use crate::bar::{Bar, Config, Format, Window};
+use crate::printer::Printer;
+use clap::ArgMatches;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// What a window looks like for a given window manager.
+pub trait Render {
+    type Window;
+
+    fn bar_window<'a>(&self, window: &'a Self::Window) -> Window<'a>;
+    /// All properties, printed as JSON.
+    fn props(&self, window: &Self::Window) -> Value;
+    /// Single property selected by its print flag.
+    fn field(&self, window: &Self::Window, flag: &str) -> String;
+}
+
+/// Non-window event, printed as JSON line.
+pub trait Event: Serialize {
+    /// Lines of the same kind replace each other when debouncing.
+    fn kind(&self) -> &'static str;
+}
+
+/// i3 and sway events, tagged with `event`.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum EventProps<C> {
+    Workspace {
+        change: C,
+        name: Option<String>,
+        num: Option<i32>,
+        output: Option<String>,
+        old: Option<String>,
+    },
+    Mode {
+        change: String,
+    },
+    Output {
+        change: String,
+        outputs: Vec<String>,
+    },
+}
+
+impl<C: Serialize> Event for EventProps<C> {
+    fn kind(&self) -> &'static str {
+        match self {
+            EventProps::Workspace { .. } => "workspace",
+            EventProps::Mode { .. } => "mode",
+            EventProps::Output { .. } => "output",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Disconnected<'a> {
+    event: &'static str,
+    error: &'a str,
+}
+
+/// Renders windows and events as selected on the command line.
+pub struct Display<R> {
+    bar: Option<Bar>,
+    printer: Printer,
+    /// Selected print flags, props are printed when there are none.
+    fields: Vec<&'static str>,
+    render: R,
+}
+
+impl<R: Render> Display<R> {
+    /// `flags` are the tool's print flags in the order they are printed.
+    pub fn from_matches(matches: &ArgMatches, flags: &[&'static str], render: R) -> Self {
+        Self {
+            bar: Format::from_matches(matches).map(|format| {
+                let config = Config::load(matches.get_one::<String>("config").map(|c| c.as_str()));
+                Bar::new(format, config)
+            }),
+            printer: Printer::from_matches(matches),
+            fields: flags
+                .iter()
+                .copied()
+                .filter(|flag| is_set(matches, flag))
+                .collect(),
+            render,
+        }
+    }
+
+    pub fn is_bar(&self) -> bool {
+        self.bar.is_some()
+    }
+
+    pub fn header(&self) {
+        if let Some(header) = self.bar.as_ref().and_then(|bar| bar.header()) {
+            println!("{header}");
+        }
+    }
+
+    pub fn event(&mut self, event: &impl Event) {
+        self.printer.print(event.kind(), json!(event).to_string());
+    }
+
+    /// Bars get an empty window, everything printed before is printed again
+    /// once the connection is back.
+    pub fn disconnected(&mut self, error: &str) {
+        if self.bar.is_some() {
+            self.no_window();
+        } else {
+            let event = Disconnected {
+                event: "disconnected",
+                error,
+            };
+            self.printer.print("disconnected", json!(event).to_string());
+        }
+        self.printer.reset();
+    }
+
+    /// Clears the bar when nothing is focused.
+    pub fn no_window(&mut self) {
+        if let Some(bar) = &self.bar {
+            let line = bar.render(&Window {
+                app: None,
+                title: None,
+            });
+            self.printer.print("window", line);
+        }
+    }
+
+    pub fn window(&mut self, window: &R::Window) {
+        let line = self.render(window);
+        self.printer.print("window", line);
+    }
+
+    /// Prints selected windows, props as one JSON array.
+    pub fn windows(&mut self, windows: &[&R::Window]) {
+        let line = if self.fields.is_empty() {
+            let props: Vec<Value> = windows.iter().map(|w| self.render.props(w)).collect();
+            json!(props).to_string()
+        } else {
+            windows
+                .iter()
+                .map(|window| self.render(window))
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+        if !line.is_empty() {
+            self.printer.print("window", line);
+        }
+    }
+
+    fn render(&self, window: &R::Window) -> String {
+        if let Some(bar) = &self.bar {
+            return bar.render(&self.render.bar_window(window));
+        }
+        if self.fields.is_empty() {
+            return self.render.props(window).to_string();
+        }
+        self.fields
+            .iter()
+            .map(|flag| self.render.field(window, flag))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// Print flag given on the command line, ones taking a value count when given
+/// bare.
+fn is_set(matches: &ArgMatches, flag: &str) -> bool {
+    match matches.try_get_one::<bool>(flag) {
+        Ok(set) => set.copied().unwrap_or_default(),
+        Err(_) => matches
+            .get_one::<String>(flag)
+            .is_some_and(|value| value.is_empty()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Titles;
+
+    impl Render for Titles {
+        type Window = (&'static str, &'static str);
+
+        fn bar_window<'a>(&self, window: &'a Self::Window) -> Window<'a> {
+            Window {
+                app: Some(window.0),
+                title: Some(window.1),
+            }
+        }
+
+        fn props(&self, window: &Self::Window) -> Value {
+            json!({ "app": window.0, "title": window.1 })
+        }
+
+        fn field(&self, window: &Self::Window, flag: &str) -> String {
+            match flag {
+                "app" => window.0.to_string(),
+                _ => window.1.to_string(),
+            }
+        }
+    }
+
+    fn display(fields: Vec<&'static str>) -> Display<Titles> {
+        Display {
+            bar: None,
+            printer: Printer::new(None),
+            fields,
+            render: Titles,
+        }
+    }
+
+    #[test]
+    fn test_props_without_selected_fields() {
+        assert_eq!(
+            display(vec![]).render(&("foot", "fish")),
+            r#"{"app":"foot","title":"fish"}"#
+        );
+    }
+
+    #[test]
+    fn test_selected_fields_in_flag_order() {
+        assert_eq!(
+            display(vec!["title", "app"]).render(&("foot", "fish")),
+            "fish\nfoot"
+        );
+    }
+}
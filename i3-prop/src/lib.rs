@@ -0,0 +1,4 @@
+pub mod bar;
+pub mod display;
+pub mod printer;
+pub mod select;
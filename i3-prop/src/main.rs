@@ -1,121 +1,225 @@
 use clap::{arg, Command};
 use i3_ipc::{
-    event::{Event, Subscribe},
-    I3Stream,
+    event::{Event, Subscribe, WindowChange, WorkspaceChange},
+    Connect, I3Stream, I3,
 };
+use i3_prop::bar::{self, Window};
+use i3_prop::display::{self, Display, Render};
+use i3_prop::printer;
+use i3_prop::select::{self, Candidate, Selector};
 use i3ipc_types::reply;
-use serde_json::json;
+use reconnect::Backoff;
+use serde::Serialize;
+use serde_json::{json, Value};
 use std::io;
 
+#[derive(Serialize)]
+struct Props<'a> {
+    id: usize,
+    #[serde(flatten)]
+    window_properties: Option<&'a reply::WindowProperties>,
+}
+
+type EventProps = display::EventProps<WorkspaceChange>;
+
 fn cli() -> Command {
     Command::new("i3-prop")
         .about("Window properties quiry tool for i3 / sway")
+        .group(bar::group())
         .args(vec![
             arg!(-l --"listen" "Listen for focus changes"),
             arg!(-t --"title" "Print window title"),
             arg!(-c --"class" "Prints window class"),
             arg!(-s --"instance-class" "Prints window instance and class"),
+            arg!(-e --"events" <EVENTS> "Comma separated event kinds to listen for")
+                .value_delimiter(',')
+                .value_parser(["window", "workspace", "mode", "output"])
+                .default_value("window"),
         ])
+        .args(bar::args())
+        .args(printer::args())
+        .args(select::args())
+        .arg(select::app_id_arg())
 }
 
 fn main() -> io::Result<()> {
     let matches = cli().get_matches();
 
-    let mut i3 = I3Stream::conn_sub([Subscribe::Window, Subscribe::Workspace])?;
-
-    let print_title = matches.get_flag("title");
-    let print_class = matches.get_flag("class");
-    let print_icls = matches.get_flag("instance-class");
-
-    let print_props = !print_title && !print_class && !print_icls;
-
-    let _ = get_focused_node(&mut i3).map_or((), |node| {
-        display_node(&node, print_props, print_title, print_class, print_icls);
-    });
-
-    if matches.get_flag("listen") {
-        for e in i3.listen() {
-            match e? {
-                Event::Window(ev) => {
-                    display_node(
-                        &ev.container,
-                        print_props,
-                        print_title,
-                        print_class,
-                        print_icls,
-                    );
-                }
-                Event::Workspace(_ev) => (),
-                Event::Output(_ev) => (),
-                Event::Mode(_ev) => (),
-                Event::BarConfig(_ev) => (),
-                Event::Binding(_ev) => (),
-                Event::Shutdown(_ev) => (),
-                Event::Tick(_ev) => (),
+    let listen = matches.get_flag("listen");
+    let events: Vec<&String> = matches
+        .get_many::<String>("events")
+        .map_or(vec![], |events| events.collect());
+    let listen_window = events.iter().any(|e| *e == "window");
+
+    let mut display = Display::from_matches(&matches, &["title", "class", "instance-class"], Nodes);
+    display.header();
+
+    let subs = subscriptions(&events, display.is_bar());
+    let mut i3 = I3Stream::conn_sub(&subs)?;
+
+    if let Some(selector) = Selector::from_matches(&matches) {
+        let tree = i3.get_tree()?;
+        let windows = get_windows(&tree)
+            .into_iter()
+            .filter(|(node, workspace)| selector.matches(&candidate(node, *workspace)))
+            .map(|(node, _)| node)
+            .collect::<Vec<&reply::Node>>();
+
+        match (selector.is_single(), windows.first()) {
+            (true, Some(node)) => display.window(node),
+            (true, None) => {
+                eprintln!("No window matches given id");
+                std::process::exit(1);
             }
+            (false, _) => display.windows(&windows),
         }
+        return Ok(());
     }
 
-    Ok(())
+    if !listen || listen_window {
+        show_focused(&mut display);
+    }
+
+    if !listen {
+        return Ok(());
+    }
+
+    let mut backoff = Backoff::new();
+    loop {
+        let error = listen_events(&mut i3, &mut display, &mut backoff);
+        eprintln!("Event stream error: {error}");
+        display.disconnected(&error.to_string());
+
+        i3 = backoff.retry(|| I3Stream::conn_sub(&subs));
+        if listen_window {
+            show_focused(&mut display);
+        }
+    }
+}
+
+/// Prints events until the connection breaks, returns what broke it.
+fn listen_events(
+    i3: &mut I3Stream,
+    display: &mut Display<Nodes>,
+    backoff: &mut Backoff,
+) -> io::Error {
+    for e in i3.listen() {
+        let event = match e {
+            Ok(event) => event,
+            Err(e) => return e,
+        };
+        backoff.reset();
+        match event {
+            // Closing the last window or switching to an empty workspace
+            // focuses no window, bars have to be cleared.
+            Event::Window(ev) if display.is_bar() && ev.change == WindowChange::Close => {
+                show_focused(display)
+            }
+            Event::Workspace(_ev) if display.is_bar() => show_focused(display),
+            // Title, mark or urgency changes of other windows are not ours.
+            Event::Window(ev) if ev.container.focused => display.window(&ev.container),
+            Event::Window(_ev) => (),
+            _ if display.is_bar() => (),
+            Event::Workspace(ev) => display.event(&EventProps::Workspace {
+                change: ev.change,
+                name: ev.current.as_ref().and_then(|n| n.name.clone()),
+                num: ev.current.as_ref().and_then(|n| n.num),
+                output: ev.current.as_ref().and_then(|n| n.output.clone()),
+                old: ev.old.as_ref().and_then(|n| n.name.clone()),
+            }),
+            Event::Output(ev) => display.event(&EventProps::Output {
+                change: ev.change,
+                outputs: get_active_outputs(),
+            }),
+            Event::Mode(ev) => display.event(&EventProps::Mode { change: ev.change }),
+            Event::BarConfig(_ev) => (),
+            Event::Binding(_ev) => (),
+            Event::Shutdown(_ev) => (),
+            Event::Tick(_ev) => (),
+        }
+    }
+    io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed")
 }
 
-fn display_node(
-    node: &reply::Node,
-    print_props: bool,
-    print_title: bool,
-    print_class: bool,
-    print_icls: bool,
-) {
-    if print_props {
-        display_props(&node.window_properties);
+/// Bars follow workspace changes too, an empty workspace has no window
+/// event to clear them.
+fn subscriptions(events: &[&String], bar: bool) -> Vec<Subscribe> {
+    let mut subs: Vec<Subscribe> = events
+        .iter()
+        .map(|event| match event.as_str() {
+            "workspace" => Subscribe::Workspace,
+            "mode" => Subscribe::Mode,
+            "output" => Subscribe::Output,
+            _ => Subscribe::Window,
+        })
+        .collect();
+    if bar && !subs.contains(&Subscribe::Workspace) {
+        subs.push(Subscribe::Workspace);
     }
-    if print_title {
-        display_title(&node.window_properties);
+    subs
+}
+
+/// i3 tree nodes as printed by the tool.
+struct Nodes;
+
+impl Render for Nodes {
+    type Window = reply::Node;
+
+    fn bar_window<'a>(&self, node: &'a reply::Node) -> Window<'a> {
+        let props = node.window_properties.as_ref();
+        Window {
+            app: props.and_then(|p| p.class.as_deref()),
+            title: props.and_then(|p| p.title.as_deref()),
+        }
     }
-    if print_class {
-        display_class(&node.window_properties);
+
+    fn props(&self, node: &reply::Node) -> Value {
+        json!(props(node))
     }
-    if print_icls {
-        display_icls(&node.window_properties);
+
+    fn field(&self, node: &reply::Node, flag: &str) -> String {
+        let window_properties = &node.window_properties;
+        match flag {
+            "title" => render_title(window_properties),
+            "class" => render_class(window_properties),
+            _ => render_icls(window_properties),
+        }
     }
 }
 
-fn display_props(window_properties: &Option<reply::WindowProperties>) {
-    println!("{}", json!(window_properties));
+fn props(node: &reply::Node) -> Props<'_> {
+    Props {
+        id: node.id,
+        window_properties: node.window_properties.as_ref(),
+    }
 }
 
-fn display_title(window_properties: &Option<reply::WindowProperties>) {
-    println!(
-        "{}",
-        window_properties
-            .clone()
-            .and_then(|p| p.title)
-            .map_or_else(String::new, |t| t)
-    );
+fn render_title(window_properties: &Option<reply::WindowProperties>) -> String {
+    window_properties
+        .clone()
+        .and_then(|p| p.title)
+        .unwrap_or_default()
 }
 
-fn display_class(window_properties: &Option<reply::WindowProperties>) {
-    println!(
-        "{}",
-        window_properties
-            .clone()
-            .and_then(|p| p.class)
-            .map_or_else(String::new, |t| t)
-    );
+fn render_class(window_properties: &Option<reply::WindowProperties>) -> String {
+    window_properties
+        .clone()
+        .and_then(|p| p.class)
+        .unwrap_or_default()
 }
 
-fn display_icls(window_properties: &Option<reply::WindowProperties>) {
-    println!(
+fn render_icls(window_properties: &Option<reply::WindowProperties>) -> String {
+    format!(
         "{} {}",
         window_properties
             .clone()
             .and_then(|p| p.instance)
-            .map_or_else(String::new, |t| t),
+            .unwrap_or_default(),
         window_properties
             .clone()
             .and_then(|p| p.class)
-            .map_or_else(String::new, |t| t)
-    );
+            .unwrap_or_default()
+    )
 }
 
 fn collect_focused<'a>(node: &'a reply::Node, mut r: Vec<&'a reply::Node>) -> Vec<&'a reply::Node> {
@@ -133,9 +237,80 @@ fn get_focused(node: &reply::Node) -> Vec<&reply::Node> {
     collect_focused(node, v)
 }
 
+/// Prints the focused window, bars are cleared when a workspace is focused.
+fn show_focused(display: &mut Display<Nodes>) {
+    let node = I3::connect()
+        .ok()
+        .and_then(|mut i3| get_focused_node(&mut i3));
+    match node {
+        Some(node) if node.window.is_some() => display.window(&node),
+        _ => display.no_window(),
+    }
+}
+
 fn get_focused_node(i3: &mut I3Stream) -> Option<reply::Node> {
     i3.get_tree().map_or(None, |tree| {
         let focused_list = get_focused(&tree);
         focused_list.first().map(|&node| node.clone())
     })
 }
+
+fn candidate<'a>(node: &'a reply::Node, workspace: Option<&'a reply::Node>) -> Candidate<'a> {
+    let window_properties = node.window_properties.as_ref();
+    Candidate {
+        id: node.id.to_string(),
+        pid: None,
+        apps: [
+            window_properties.and_then(|p| p.class.as_deref()),
+            window_properties.and_then(|p| p.instance.as_deref()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+        workspaces: workspace.map_or(vec![], |ws| {
+            [ws.name.clone(), ws.num.map(|num| num.to_string())]
+                .into_iter()
+                .flatten()
+                .collect()
+        }),
+    }
+}
+
+/// Collect all windows together with the workspace they are on.
+fn collect_windows<'a>(
+    node: &'a reply::Node,
+    workspace: Option<&'a reply::Node>,
+    mut r: Vec<(&'a reply::Node, Option<&'a reply::Node>)>,
+) -> Vec<(&'a reply::Node, Option<&'a reply::Node>)> {
+    let workspace = match node.node_type {
+        reply::NodeType::Workspace => Some(node),
+        _ => workspace,
+    };
+    if node.window.is_some() {
+        r.push((node, workspace));
+    }
+    for n in &node.nodes {
+        r = collect_windows(n, workspace, r);
+    }
+    for n in &node.floating_nodes {
+        r = collect_windows(n, workspace, r);
+    }
+    r
+}
+
+fn get_windows(node: &reply::Node) -> Vec<(&reply::Node, Option<&reply::Node>)> {
+    collect_windows(node, None, vec![])
+}
+
+/// Output events carry no details, so ask i3 which outputs are active now.
+fn get_active_outputs() -> Vec<String> {
+    I3::connect()
+        .and_then(|mut i3| i3.get_outputs())
+        .map_or(vec![], |outputs| {
+            outputs
+                .into_iter()
+                .filter(|output| output.active)
+                .map(|output| output.name)
+                .collect()
+        })
+}
@@ -0,0 +1,120 @@
+use clap::{arg, value_parser, Arg, ArgMatches};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+type Line = (&'static str, String);
+
+enum Message {
+    Print(Line),
+    Reset,
+}
+
+/// Prints rendered lines, skipping ones identical to the last line of the same
+/// kind. With a debounce delay lines are held until events calm down, so only
+/// the latest line of every kind gets printed.
+pub struct Printer {
+    last: HashMap<&'static str, String>,
+    sender: Option<Sender<Message>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Printer {
+    pub fn new(debounce: Option<Duration>) -> Self {
+        let (sender, thread) = match debounce {
+            Some(delay) => {
+                let (sender, receiver) = mpsc::channel();
+                let thread = thread::spawn(move || debounce_lines(receiver, delay));
+                (Some(sender), Some(thread))
+            }
+            None => (None, None),
+        };
+        Self {
+            last: HashMap::new(),
+            sender,
+            thread,
+        }
+    }
+
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        let debounce = matches
+            .get_one::<u64>("debounce")
+            .map(|ms| Duration::from_millis(*ms));
+        Self::new(debounce)
+    }
+
+    pub fn print(&mut self, kind: &'static str, line: String) {
+        match &self.sender {
+            Some(sender) => sender
+                .send(Message::Print((kind, line)))
+                .unwrap_or_default(),
+            None => print_changed(&mut self.last, kind, line),
+        }
+    }
+
+    /// Forgets printed lines, so the next line of every kind gets printed even
+    /// if it is the same as before, e.g. after reconnecting.
+    pub fn reset(&mut self) {
+        match &self.sender {
+            Some(sender) => sender.send(Message::Reset).unwrap_or_default(),
+            None => self.last.clear(),
+        }
+    }
+}
+
+impl Drop for Printer {
+    /// Let the debounce thread flush what is still pending.
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            thread.join().unwrap_or_default();
+        }
+    }
+}
+
+fn print_changed(last: &mut HashMap<&'static str, String>, kind: &'static str, line: String) {
+    if last.get(kind) != Some(&line) {
+        println!("{line}");
+        last.insert(kind, line);
+    }
+}
+
+fn debounce_lines(receiver: Receiver<Message>, delay: Duration) {
+    let mut last = HashMap::new();
+    let mut pending: Vec<Line> = vec![];
+
+    loop {
+        let received = if pending.is_empty() {
+            receiver.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            receiver.recv_timeout(delay)
+        };
+
+        match received {
+            Ok(Message::Print((kind, line))) => {
+                pending.retain(|(pending_kind, _)| *pending_kind != kind);
+                pending.push((kind, line));
+            }
+            Ok(Message::Reset) => last.clear(),
+            Err(RecvTimeoutError::Timeout) => {
+                for (kind, line) in pending.drain(..) {
+                    print_changed(&mut last, kind, line);
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                for (kind, line) in pending.drain(..) {
+                    print_changed(&mut last, kind, line);
+                }
+                break;
+            }
+        }
+    }
+}
+
+pub fn args() -> Vec<Arg> {
+    vec![
+        arg!(--"debounce" <MS> "Wait for events to settle for given milliseconds before printing")
+            .value_parser(value_parser!(u64)),
+    ]
+}
@@ -0,0 +1,182 @@
+use clap::{arg, value_parser, Arg, ArgMatches};
+use regex::Regex;
+
+/// Window as seen by the selector, every tool fills in what it knows.
+pub struct Candidate<'a> {
+    pub id: String,
+    pub pid: Option<i32>,
+    /// App id, class, instance - any of them can match `--app` / `--app-id`.
+    pub apps: Vec<&'a str>,
+    /// Name, number or index - any of them can match `--workspace`.
+    pub workspaces: Vec<String>,
+}
+
+/// Selects windows other than the focused one. All given criteria have to match.
+pub struct Selector {
+    pub id: Option<String>,
+    pub pid: Option<i32>,
+    pub app: Option<Regex>,
+    pub workspace: Option<String>,
+}
+
+impl Selector {
+    /// Selector built from command line, `None` when no selector was given.
+    pub fn from_matches(matches: &ArgMatches) -> Option<Selector> {
+        let selector = Selector {
+            id: matches.get_one::<String>("id").cloned(),
+            pid: matches.try_get_one::<i32>("pid").ok().flatten().copied(),
+            app: app_pattern(matches).map(|app| {
+                Regex::new(app).unwrap_or_else(|e| {
+                    eprintln!("Invalid app regex: {e}");
+                    std::process::exit(1);
+                })
+            }),
+            workspace: matches.get_one::<String>("workspace").cloned(),
+        };
+        let selected = matches.get_flag("all")
+            || selector.id.is_some()
+            || selector.pid.is_some()
+            || selector.app.is_some()
+            || selector.workspace.is_some();
+
+        selected.then_some(selector)
+    }
+
+    /// Id selects exactly one window, printed on its own instead of in a list.
+    pub fn is_single(&self) -> bool {
+        self.id.is_some()
+    }
+
+    pub fn matches(&self, candidate: &Candidate) -> bool {
+        self.id.as_ref().is_none_or(|id| *id == candidate.id)
+            && self.pid.is_none_or(|pid| Some(pid) == candidate.pid)
+            && self
+                .app
+                .as_ref()
+                .is_none_or(|app| candidate.apps.iter().any(|a| app.is_match(a)))
+            && self
+                .workspace
+                .as_ref()
+                .is_none_or(|ws| candidate.workspaces.contains(ws))
+    }
+}
+
+/// `--app`, or `--app-id` given a value.
+fn app_pattern(matches: &ArgMatches) -> Option<&String> {
+    let app_id = matches.try_get_one::<String>("app-id").ok().flatten();
+    matches
+        .get_one::<String>("app")
+        .or(app_id.filter(|app_id| !app_id.is_empty()))
+}
+
+/// Selector arguments, `--pid` is separate as not every window manager knows it.
+pub fn args() -> Vec<Arg> {
+    vec![
+        arg!(--"id" <ID> "Select window by id"),
+        arg!(--"app" <REGEX> "Select windows with matching app id / class"),
+        arg!(--"workspace" <WORKSPACE> "Select windows on workspace"),
+        arg!(--"all" "Select all windows"),
+    ]
+    .into_iter()
+    .map(|arg| arg.conflicts_with_all(["listen", "bar"]))
+    .collect()
+}
+
+/// `--app-id` selector for tools that do not print app ids.
+pub fn app_id_arg() -> Arg {
+    arg!(--"app-id" <REGEX> "Select windows with matching app id / class")
+        .conflicts_with_all(["app", "listen", "bar"])
+}
+
+/// `-i --app-id` of tools printing app ids, given a regex it selects windows
+/// like `--app` instead.
+pub fn print_app_id_arg() -> Arg {
+    arg!(-i --"app-id" [REGEX] "Prints window app id, with REGEX selects windows with matching one")
+        .default_missing_value("")
+        .conflicts_with("app")
+}
+
+pub fn pid_arg() -> Arg {
+    arg!(--"pid" <PID> "Select windows of process")
+        .value_parser(value_parser!(i32))
+        .conflicts_with_all(["listen", "bar"])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn candidate() -> Candidate<'static> {
+        Candidate {
+            id: "12".to_string(),
+            pid: Some(1234),
+            apps: vec!["org.wezfurlong.wezterm"],
+            workspaces: vec!["3".to_string(), "3:term".to_string()],
+        }
+    }
+
+    fn selector() -> Selector {
+        Selector {
+            id: None,
+            pid: None,
+            app: None,
+            workspace: None,
+        }
+    }
+
+    fn parse(arg: Arg, argv: &[&str]) -> ArgMatches {
+        clap::Command::new("prop")
+            .group(crate::bar::group())
+            .args(crate::bar::args())
+            .arg(arg!(--"listen"))
+            .arg(arg!(-t - -"title"))
+            .args(args())
+            .arg(arg)
+            .get_matches_from([&["prop"], argv].concat())
+    }
+
+    #[test]
+    fn test_app_id_selects_given_regex() {
+        let matches = parse(app_id_arg(), &["--app-id", "^foot$"]);
+        let selector = Selector::from_matches(&matches).unwrap();
+        assert!(selector.app.unwrap().is_match("foot"));
+
+        let matches = parse(print_app_id_arg(), &["--app-id", "^foot$"]);
+        let selector = Selector::from_matches(&matches).unwrap();
+        assert!(selector.app.unwrap().is_match("foot"));
+    }
+
+    #[test]
+    fn test_bare_app_id_only_prints() {
+        let matches = parse(print_app_id_arg(), &["-i", "-t"]);
+        assert!(Selector::from_matches(&matches).is_none());
+        assert_eq!(matches.get_one::<String>("app-id").unwrap(), "");
+        assert!(matches.get_flag("title"));
+    }
+
+    #[test]
+    fn test_empty_selector_matches_all() {
+        assert!(selector().matches(&candidate()));
+    }
+
+    #[test]
+    fn test_all_criteria_have_to_match() {
+        let mut selector = selector();
+        selector.app = Some(Regex::new("wezterm$").unwrap());
+        selector.workspace = Some("3".to_string());
+        assert!(selector.matches(&candidate()));
+
+        selector.pid = Some(1);
+        assert!(!selector.matches(&candidate()));
+    }
+
+    #[test]
+    fn test_workspace_matches_any_identifier() {
+        let mut selector = selector();
+        selector.workspace = Some("3:term".to_string());
+        assert!(selector.matches(&candidate()));
+
+        selector.workspace = Some("term".to_string());
+        assert!(!selector.matches(&candidate()));
+    }
+}
@@ -0,0 +1,203 @@
+use clap::Command;
+use i3_ipc::{Connect, I3Stream, I3};
+use i3ipc_types::event::{Event, Subscribe, WorkspaceChange};
+use i3ipc_types::reply;
+use std::io;
+use sway_workspace::config::Config;
+use sway_workspace::scratch::{self, Scratchpad, State, Window};
+use sway_workspace::{Compositor, FocusEvents, Options, Target, Workspace};
+
+fn cli() -> Command {
+    Command::new("i3-workspace")
+        .about("Manage workspaces in i3")
+        .args(sway_workspace::args())
+        .subcommand(scratch::cli())
+}
+
+struct I3Wm {
+    i3: I3Stream,
+    dry_run: bool,
+}
+
+impl I3Wm {
+    fn command(&mut self, command: String) -> io::Result<()> {
+        if self.dry_run {
+            println!("{command}");
+            return Ok(());
+        }
+        for outcome in self.i3.run_command(command)? {
+            if !outcome.success {
+                let error = outcome.error.unwrap_or_else(|| "Command failed".into());
+                return Err(io::Error::other(error));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Compositor for I3Wm {
+    type Error = io::Error;
+
+    fn workspaces(&mut self) -> io::Result<Vec<Workspace>> {
+        // i3 does not list workspace contents, so look for them in the tree.
+        let tree = self.i3.get_tree()?;
+        let used = used_workspaces(&tree, vec![]);
+
+        let mut workspaces: Vec<Workspace> = self
+            .i3
+            .get_workspaces()?
+            .into_iter()
+            .map(|ws| Workspace {
+                id: ws.id as i64,
+                empty: !used.contains(&ws.name),
+                num: (ws.num >= 0).then_some(ws.num),
+                name: ws.name,
+                output: ws.output,
+                focused: ws.focused,
+                visible: ws.visible,
+            })
+            .collect();
+        sway_workspace::sort_by_num(&mut workspaces);
+        Ok(workspaces)
+    }
+
+    fn focus(&mut self, target: &Target) -> io::Result<()> {
+        self.command(format!(
+            "workspace --no-auto-back-and-forth \"{}\"",
+            target.name()
+        ))
+    }
+
+    fn move_window(&mut self, target: &Target) -> io::Result<()> {
+        self.command(format!("move container to workspace \"{}\"", target.name()))
+    }
+
+    fn rename(&mut self, ws: &Workspace, name: &str) -> io::Result<()> {
+        self.command(format!("rename workspace \"{}\" to \"{name}\"", ws.name))
+    }
+
+    fn outputs(&mut self) -> io::Result<Vec<String>> {
+        let mut outputs = self.i3.get_outputs()?;
+        outputs.retain(|output| output.active);
+        outputs.sort_by_key(|output| (output.rect.x, output.rect.y));
+        Ok(outputs.into_iter().map(|output| output.name).collect())
+    }
+
+    fn move_workspace(&mut self, ws: &Workspace, output: &str) -> io::Result<()> {
+        self.command(format!(
+            "workspace --no-auto-back-and-forth \"{}\"; move workspace to output \"{output}\"",
+            ws.name
+        ))
+    }
+
+    fn focus_events(&mut self) -> io::Result<FocusEvents<io::Error>> {
+        let mut events = I3Stream::conn_sub([Subscribe::Workspace])?;
+        Ok(Box::new(
+            std::iter::from_fn(move || Some(events.receive_event())).filter_map(
+                |event| match event {
+                    Ok(Event::Workspace(ev)) if ev.change == WorkspaceChange::Focus => Some(Ok(())),
+                    Ok(_) => None,
+                    Err(e) => Some(Err(e)),
+                },
+            ),
+        ))
+    }
+}
+
+impl Scratchpad for I3Wm {
+    type Error = io::Error;
+
+    fn state(&mut self) -> io::Result<State> {
+        let windows = collect_windows(&self.i3.get_tree()?, "", vec![]);
+        let focused = self
+            .i3
+            .get_workspaces()?
+            .into_iter()
+            .find(|ws| ws.focused)
+            .ok_or_else(|| io::Error::other("No focused workspace"))?;
+        let rect = self
+            .i3
+            .get_outputs()?
+            .into_iter()
+            .find(|output| output.name == focused.output)
+            .map(|output| output.rect)
+            .unwrap_or(focused.rect);
+        Ok(State {
+            windows,
+            workspace: focused.name,
+            output: scratch::Output {
+                name: focused.output,
+                width: rect.width as i32,
+                height: rect.height as i32,
+            },
+        })
+    }
+
+    fn command(&mut self, command: String) -> io::Result<()> {
+        I3Wm::command(self, command)
+    }
+}
+
+/// Windows in the node with the workspace they are on, scratchpad included.
+fn collect_windows(node: &reply::Node, workspace: &str, mut r: Vec<Window>) -> Vec<Window> {
+    let workspace = match node.node_type {
+        reply::NodeType::Workspace => node.name.as_deref().unwrap_or_default(),
+        _ => workspace,
+    };
+    if node.window.is_some() {
+        r.push(Window {
+            id: node.id as i64,
+            app_id: None,
+            class: node
+                .window_properties
+                .as_ref()
+                .and_then(|p| p.class.clone()),
+            title: node.name.clone(),
+            workspace: workspace.to_string(),
+            focused: node.focused,
+        });
+    }
+    for n in node.nodes.iter().chain(&node.floating_nodes) {
+        r = collect_windows(n, workspace, r);
+    }
+    r
+}
+
+/// Names of workspaces with at least one tiled or floating container.
+fn used_workspaces(node: &reply::Node, mut r: Vec<String>) -> Vec<String> {
+    if node.node_type == reply::NodeType::Workspace {
+        if !node.nodes.is_empty() || !node.floating_nodes.is_empty() {
+            r.extend(node.name.clone());
+        }
+        return r;
+    }
+    for n in &node.nodes {
+        r = used_workspaces(n, r);
+    }
+    r
+}
+
+fn main() {
+    let matches = cli().get_matches();
+    let i3 = I3::connect().unwrap_or_else(|e| {
+        eprintln!("Can not connect to i3 ipc: {e}");
+        std::process::exit(1);
+    });
+
+    let options = Options::from_matches(&matches);
+    let mut i3 = I3Wm {
+        i3,
+        dry_run: options.dry_run,
+    };
+
+    if let Some(scratch) = matches.subcommand_matches("scratch") {
+        let config = Config::load(matches.get_one::<String>("config").map(|c| c.as_str()));
+        let name = scratch.get_one::<String>("NAME").unwrap();
+        let scratch = scratch::from_config(&config, name);
+        let result = scratch::run(&mut i3, scratch, options.dry_run).map(|_| None);
+        sway_workspace::report(result, &options);
+        return;
+    }
+
+    sway_workspace::report(sway_workspace::run(&mut i3, &options), &options);
+}
@@ -0,0 +1,258 @@
+use niri_ipc::SizeChange;
+use regex::Regex;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+/// Layout rules, read from
+/// `$XDG_CONFIG_HOME/i-use-rust-btw/niri-layout-czar.toml`:
+///
+/// ```toml
+/// # column widths for 1, 2, 3... tiled windows, the last one is used for
+/// # any more, "maximize" only applies to a sole window and its previous
+/// # width is restored when more windows arrive
+/// widths = ["maximize"]
+/// # app ids never resized and not counted
+/// exclude = ["^pavucontrol$"]
+///
+/// [[rules]]
+/// output = "DP-1"
+/// widths = ["100%", "50%", "33.333%"]
+///
+/// [[rules]]
+/// workspace = "chat"
+/// enabled = false
+///
+/// # sole window on outputs this wide gets a centered column instead
+/// [center]
+/// min-width = 3840
+/// width = "60%"
+/// ```
+///
+/// First rule whose given `workspace`, `output` and `app-id` all match wins,
+/// `app-id` matches when any tiled window on the workspace does. Top level
+/// settings apply when no rule matches.
+#[derive(Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+    pub enabled: bool,
+    pub widths: Vec<Preset>,
+    pub exclude: Vec<Pattern>,
+    pub rules: Vec<Rule>,
+    pub center: Option<Center>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Rule {
+    pub workspace: Option<String>,
+    pub output: Option<String>,
+    pub app_id: Option<Pattern>,
+    pub enabled: Option<bool>,
+    /// Top level widths when not given.
+    pub widths: Option<Vec<Preset>>,
+}
+
+/// Centered column for a sole window on wide outputs.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Center {
+    /// Logical width of the output.
+    pub min_width: f64,
+    #[serde(deserialize_with = "size_change")]
+    pub width: SizeChange,
+}
+
+/// Column width applied for a number of windows.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Preset {
+    Maximize,
+    Width(SizeChange),
+}
+
+/// Regex checked when the config is loaded.
+#[derive(Deserialize)]
+#[serde(try_from = "String")]
+pub struct Pattern(Regex);
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            enabled: true,
+            widths: vec![Preset::Maximize],
+            exclude: vec![],
+            rules: vec![],
+            center: None,
+        }
+    }
+}
+
+impl Config {
+    /// Load config from the default location. Missing file means defaults,
+    /// broken one is reported and ends the process.
+    pub fn load() -> Config {
+        let path = default_config_path();
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return Config::default(),
+        };
+        toml::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("Can not parse config {path}: {e}");
+            std::process::exit(1);
+        })
+    }
+
+    pub fn is_excluded(&self, app_id: Option<&str>) -> bool {
+        let app_id = app_id.unwrap_or_default();
+        self.exclude
+            .iter()
+            .any(|pattern| pattern.0.is_match(app_id))
+    }
+
+    /// Width presets for the workspace, `None` when it is left alone.
+    pub fn widths(
+        &self,
+        workspace: Option<&str>,
+        output: Option<&str>,
+        app_ids: &[&str],
+    ) -> Option<&[Preset]> {
+        let rule = self.rules.iter().find(|rule| {
+            rule.workspace
+                .as_deref()
+                .is_none_or(|name| workspace == Some(name))
+                && rule
+                    .output
+                    .as_deref()
+                    .is_none_or(|name| output == Some(name))
+                && rule
+                    .app_id
+                    .as_ref()
+                    .is_none_or(|pattern| app_ids.iter().any(|id| pattern.0.is_match(id)))
+        });
+        let (enabled, widths) = match rule {
+            Some(rule) => (
+                rule.enabled.unwrap_or(true),
+                rule.widths.as_ref().unwrap_or(&self.widths),
+            ),
+            None => (self.enabled, &self.widths),
+        };
+        enabled.then_some(widths.as_slice())
+    }
+
+    /// Width of the centered column for a sole window on an output of given
+    /// width, `None` when it is too narrow.
+    pub fn center(&self, output_width: f64) -> Option<SizeChange> {
+        self.center
+            .as_ref()
+            .filter(|center| output_width >= center.min_width)
+            .map(|center| center.width)
+    }
+}
+
+/// Preset for `count` tiled windows.
+pub fn preset(widths: &[Preset], count: usize) -> Option<Preset> {
+    let preset = widths.get(count.checked_sub(1)?).or(widths.last())?;
+    match preset {
+        Preset::Maximize if count > 1 => None,
+        preset => Some(*preset),
+    }
+}
+
+impl TryFrom<String> for Preset {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if value == "maximize" {
+            return Ok(Preset::Maximize);
+        }
+        value
+            .parse()
+            .map(Preset::Width)
+            .map_err(|e| format!("invalid width {value}: {e}"))
+    }
+}
+
+impl TryFrom<String> for Pattern {
+    type Error = regex::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Regex::new(&value).map(Pattern)
+    }
+}
+
+fn size_change<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SizeChange, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(D::Error::custom)
+}
+
+fn default_config_path() -> String {
+    let dir = std::env::var("XDG_CONFIG_HOME").unwrap_or_else(|_| {
+        let home = std::env::var("HOME").unwrap_or("/tmp".into());
+        format!("{home}/.config")
+    });
+    format!("{dir}/i-use-rust-btw/niri-layout-czar.toml")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rules_from_toml() {
+        let config: Config = toml::from_str(
+            "exclude = [\"^pavucontrol$\"]\n\
+             [[rules]]\nworkspace = \"chat\"\nenabled = false\n\
+             [[rules]]\noutput = \"DP-1\"\nwidths = [\"100%\", \"50%\"]\n\
+             [[rules]]\napp-id = \"^firefox$\"\nwidths = [\"80%\"]\n",
+        )
+        .unwrap();
+        assert!(config.is_excluded(Some("pavucontrol")));
+        assert!(!config.is_excluded(None));
+
+        assert_eq!(config.widths(Some("chat"), Some("DP-1"), &[]), None);
+        assert_eq!(
+            config.widths(Some("1"), Some("DP-1"), &[]),
+            Some(
+                &[
+                    Preset::Width(SizeChange::SetProportion(100.0)),
+                    Preset::Width(SizeChange::SetProportion(50.0)),
+                ][..]
+            )
+        );
+        assert_eq!(
+            config.widths(None, Some("eDP-1"), &["kitty", "firefox"]),
+            Some(&[Preset::Width(SizeChange::SetProportion(80.0))][..])
+        );
+        assert_eq!(
+            config.widths(None, Some("eDP-1"), &["kitty"]),
+            Some(&[Preset::Maximize][..])
+        );
+        assert!(toml::from_str::<Config>("widths = [\"wide\"]").is_err());
+    }
+
+    #[test]
+    fn test_center_on_wide_outputs() {
+        let config: Config =
+            toml::from_str("[center]\nmin-width = 3840\nwidth = \"60%\"\n").unwrap();
+        assert_eq!(config.center(5120.0), Some(SizeChange::SetProportion(60.0)));
+        assert_eq!(config.center(3840.0), Some(SizeChange::SetProportion(60.0)));
+        assert_eq!(config.center(1920.0), None);
+        assert_eq!(Config::default().center(5120.0), None);
+    }
+
+    #[test]
+    fn test_preset_for_count() {
+        let widths = [
+            Preset::Width(SizeChange::SetProportion(100.0)),
+            Preset::Width(SizeChange::SetProportion(50.0)),
+        ];
+        assert_eq!(preset(&widths, 0), None);
+        assert_eq!(preset(&widths, 1), Some(widths[0]));
+        assert_eq!(preset(&widths, 3), Some(widths[1]));
+
+        assert_eq!(preset(&[Preset::Maximize], 1), Some(Preset::Maximize));
+        assert_eq!(preset(&[Preset::Maximize], 2), None);
+        assert_eq!(preset(&[], 1), None);
+    }
+}
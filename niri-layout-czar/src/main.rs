@@ -1,110 +1,191 @@
+mod config;
+
+use config::{Config, Preset};
 use niri_ipc::socket::Socket;
-use niri_ipc::{Action, Event, Output, Request, Response, Window, Workspace};
-use std::collections::HashMap;
+use niri_ipc::{
+    Action, Event, Output, Reply, Request, Response, SizeChange, Window, WindowLayout, Workspace,
+};
+use reconnect::Backoff;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::rc::Rc;
 
 /// Per-window info we track.
 struct WindowInfo {
     workspace_id: Option<u64>,
     is_floating: bool,
-    tile_width: f64,
+    /// Excluded by config, left alone and not counted.
+    excluded: bool,
+    app_id: Option<String>,
+    window_width: i32,
+}
+
+impl WindowInfo {
+    fn is_tiled(&self) -> bool {
+        !self.is_floating && !self.excluded
+    }
+}
+
+/// Share of the output width a reported width may be off from the one we
+/// asked for, gaps and borders are not ours to know.
+const TOLERANCE: f64 = 0.05;
+
+/// Width change we made, to tell it apart from the user's.
+struct Resize {
+    /// Window width our change should end up with.
+    expected: i32,
+    tolerance: i32,
+    /// Whether niri reported the expected width yet, widths before are stale.
+    landed: bool,
+}
+
+impl Resize {
+    fn is_expected(&self, width: i32) -> bool {
+        (width - self.expected).abs() <= self.tolerance
+    }
 }
 
 /// Daemon state.
 struct State {
+    config: Rc<Config>,
     /// window_id -> info
     windows: HashMap<u64, WindowInfo>,
     /// workspace_id -> output name
     workspace_output: HashMap<u64, String>,
+    /// workspace_id -> name, for named workspaces
+    workspace_name: HashMap<u64, String>,
     /// output name -> logical width
     output_width: HashMap<String, f64>,
     /// workspace_id -> previous tiled window count
     prev_tiled_count: HashMap<u64, usize>,
-    /// workspace_id -> the sole tiled window id (when count was 1)
-    sole_window: HashMap<u64, u64>,
+    /// window_id -> our last resize of the window
+    sized: HashMap<u64, Resize>,
+    /// window_id -> window width before we maximized or centered it
+    restore: HashMap<u64, i32>,
+    /// windows the user resized after us, left alone from then on
+    manual: HashSet<u64>,
     /// currently focused window id
     focused_id: Option<u64>,
+    /// actions decided on, sent by the event loop
+    actions: Vec<Action>,
 }
 
 impl State {
-    fn new() -> Self {
+    fn new(config: Rc<Config>) -> Self {
         Self {
+            config,
             windows: HashMap::new(),
             workspace_output: HashMap::new(),
+            workspace_name: HashMap::new(),
             output_width: HashMap::new(),
             prev_tiled_count: HashMap::new(),
-            sole_window: HashMap::new(),
+            sized: HashMap::new(),
+            restore: HashMap::new(),
+            manual: HashSet::new(),
             focused_id: None,
+            actions: vec![],
         }
     }
 
     fn update_workspaces(&mut self, workspaces: &[Workspace]) {
+        let before = self.workspace_outputs();
         self.workspace_output.clear();
+        self.workspace_name.clear();
         for ws in workspaces {
+            if let Some(name) = &ws.name {
+                self.workspace_name.insert(ws.id, name.clone());
+            }
             if let Some(output) = &ws.output {
                 self.workspace_output.insert(ws.id, output.clone());
             }
         }
+        self.forget_moved_resizes(&before);
     }
 
     fn update_outputs(&mut self, outputs: &HashMap<String, Output>) {
+        let before = self.workspace_outputs();
         self.output_width.clear();
         for (name, o) in outputs {
             if let Some(logical) = &o.logical {
                 self.output_width.insert(name.clone(), logical.width as f64);
             }
         }
+        self.forget_moved_resizes(&before);
+    }
+
+    /// workspace_id -> output name and width
+    fn workspace_outputs(&self) -> HashMap<u64, (String, Option<f64>)> {
+        self.workspace_output
+            .iter()
+            .map(|(&ws_id, output)| (ws_id, (output.clone(), self.workspace_width(ws_id))))
+            .collect()
+    }
+
+    /// Windows on workspaces that moved to another output, or whose output
+    /// changed width, get a new width from niri that is not the user's.
+    fn forget_moved_resizes(&mut self, before: &HashMap<u64, (String, Option<f64>)>) {
+        let after = self.workspace_outputs();
+        let windows = &self.windows;
+        self.sized.retain(|id, _| {
+            windows
+                .get(id)
+                .and_then(|info| info.workspace_id)
+                .is_some_and(|ws_id| before.get(&ws_id) == after.get(&ws_id))
+        });
     }
 
     /// Rebuild state from a full window list (initial sync).
     fn reset_windows(&mut self, windows: &[Window]) {
         self.windows.clear();
+        self.focused_id = None;
         for w in windows {
-            self.windows.insert(
-                w.id,
-                WindowInfo {
-                    workspace_id: w.workspace_id,
-                    is_floating: w.is_floating,
-                    tile_width: w.layout.tile_size.0,
-                },
-            );
+            self.windows.insert(w.id, self.window_info(w));
             if w.is_focused {
                 self.focused_id = Some(w.id);
             }
         }
     }
 
-    /// Update or insert a window. Returns the old workspace_id if the window moved.
-    fn upsert_window(&mut self, w: &Window) -> Option<(u64, Option<u64>)> {
-        let old_ws = self.windows.get(&w.id).and_then(|info| info.workspace_id);
-        let new_ws = w.workspace_id;
-        let moved = match (old_ws, new_ws) {
-            (Some(old), Some(new)) if old != new => Some((w.id, old_ws)),
-            (None, Some(_)) => {
-                // Could be a new window or first time seeing it; check if we knew it before.
-                if self.windows.contains_key(&w.id) {
-                    Some((w.id, old_ws))
-                } else {
-                    None
-                }
+    /// Take a full window list without acting on it, so we don't spuriously
+    /// act on startup state.
+    fn sync_windows(&mut self, windows: &[Window]) {
+        self.reset_windows(windows);
+        self.prev_tiled_count = self.snapshot_counts();
+        let windows = &self.windows;
+        self.sized.retain(|id, _| windows.contains_key(id));
+        self.restore.retain(|id, _| windows.contains_key(id));
+        self.manual.retain(|id| windows.contains_key(id));
+    }
+
+    /// Update or insert a window. Our resize is forgotten when the window
+    /// changes workspace, its width is up to the new one.
+    fn upsert_window(&mut self, w: &Window) {
+        let info = self.window_info(w);
+        if let Some(old) = self.windows.insert(w.id, info) {
+            if old.workspace_id != w.workspace_id {
+                self.sized.remove(&w.id);
             }
-            _ => None,
-        };
-        self.windows.insert(
-            w.id,
-            WindowInfo {
-                workspace_id: w.workspace_id,
-                is_floating: w.is_floating,
-                tile_width: w.layout.tile_size.0,
-            },
-        );
+        }
         if w.is_focused {
             self.focused_id = Some(w.id);
         }
-        moved
+    }
+
+    fn window_info(&self, w: &Window) -> WindowInfo {
+        WindowInfo {
+            workspace_id: w.workspace_id,
+            is_floating: w.is_floating,
+            excluded: self.config.is_excluded(w.app_id.as_deref()),
+            app_id: w.app_id.clone(),
+            window_width: w.layout.window_size.0,
+        }
     }
 
     fn remove_window(&mut self, id: u64) {
         self.windows.remove(&id);
+        self.sized.remove(&id);
+        self.restore.remove(&id);
+        self.manual.remove(&id);
         if self.focused_id == Some(id) {
             self.focused_id = None;
         }
@@ -116,36 +197,109 @@ impl State {
         self.output_width.get(output).copied()
     }
 
-    /// Check if a window's column appears maximized (tile fills output width).
-    fn is_maximized(&self, win_id: u64) -> bool {
-        let info = match self.windows.get(&win_id) {
-            Some(i) => i,
-            None => return false,
+    /// Width of the centered column for a sole window, if the workspace's
+    /// output is wide enough.
+    fn center_width(&self, ws_id: u64) -> Option<SizeChange> {
+        self.config.center(self.workspace_width(ws_id)?)
+    }
+
+    /// Track a layout reported by niri. Fullscreen windows cover the output,
+    /// their width says nothing about the column.
+    fn update_layout(&mut self, win_id: u64, layout: &WindowLayout) {
+        let fullscreen = self
+            .windows
+            .get(&win_id)
+            .and_then(|info| info.workspace_id)
+            .and_then(|ws_id| self.workspace_width(ws_id))
+            .is_some_and(|width| layout.tile_size.0 >= width);
+        if !fullscreen {
+            self.update_width(win_id, layout.window_size.0);
+        }
+    }
+
+    /// Track a window width reported by niri. A window we resized that
+    /// changes away from the width we asked for was resized by the user.
+    fn update_width(&mut self, win_id: u64, width: i32) {
+        if let Some(info) = self.windows.get_mut(&win_id) {
+            info.window_width = width;
+        }
+        let Some(resize) = self.sized.get_mut(&win_id) else {
+            return;
         };
-        let ws_id = match info.workspace_id {
-            Some(id) => id,
-            None => return false,
+        if resize.is_expected(width) {
+            resize.landed = true;
+        } else if resize.landed {
+            self.sized.remove(&win_id);
+            self.restore.remove(&win_id);
+            self.manual.insert(win_id);
+        }
+        // Otherwise layout from before our change got through.
+    }
+
+    /// Expect niri to report the width the change gives on the window's
+    /// output. Without a known output width there is nothing to compare to.
+    fn start_resize(&mut self, win_id: u64, change: SizeChange) {
+        let Some(info) = self.windows.get(&win_id) else {
+            return;
+        };
+        let output_width = info
+            .workspace_id
+            .and_then(|ws_id| self.workspace_width(ws_id));
+        let Some(output_width) = output_width else {
+            self.sized.remove(&win_id);
+            return;
         };
-        let output_w = match self.workspace_width(ws_id) {
-            Some(w) => w,
-            None => return false,
+        let mut resize = Resize {
+            expected: expected_width(change, info.window_width, output_width),
+            tolerance: (output_width * TOLERANCE) as i32,
+            landed: false,
         };
-        info.tile_width >= output_w * 0.95
+        // niri reports nothing when the width does not change.
+        resize.landed = resize.is_expected(info.window_width);
+        self.sized.insert(win_id, resize);
     }
 
-    /// Count tiled (non-floating) windows on a workspace.
-    fn tiled_count(&self, ws_id: u64) -> usize {
-        self.windows
+    /// Remember the width to restore, unless we already changed it.
+    fn remember_width(&mut self, win_id: u64) {
+        if let Some(info) = self.windows.get(&win_id) {
+            self.restore.entry(win_id).or_insert(info.window_width);
+        }
+    }
+
+    /// Width presets configured for a workspace, `None` when disabled there.
+    fn widths(&self, ws_id: u64) -> Option<&[Preset]> {
+        let app_ids: Vec<&str> = self
+            .windows
             .values()
-            .filter(|info| info.workspace_id == Some(ws_id) && !info.is_floating)
-            .count()
+            .filter(|info| info.workspace_id == Some(ws_id) && info.is_tiled())
+            .filter_map(|info| info.app_id.as_deref())
+            .collect();
+        self.config.widths(
+            self.workspace_name.get(&ws_id).map(String::as_str),
+            self.workspace_output.get(&ws_id).map(String::as_str),
+            &app_ids,
+        )
+    }
+
+    /// Tiled (non-floating, not excluded) windows on a workspace.
+    fn tiled_windows(&self, ws_id: u64) -> Vec<u64> {
+        self.windows
+            .iter()
+            .filter(|(_, info)| info.workspace_id == Some(ws_id) && info.is_tiled())
+            .map(|(&win_id, _)| win_id)
+            .collect()
+    }
+
+    /// Count tiled windows on a workspace.
+    fn tiled_count(&self, ws_id: u64) -> usize {
+        self.tiled_windows(ws_id).len()
     }
 
     /// Get the sole tiled window on a workspace, if exactly one.
     fn sole_tiled_window(&self, ws_id: u64) -> Option<u64> {
         let mut found = None;
         for (&win_id, info) in &self.windows {
-            if info.workspace_id == Some(ws_id) && !info.is_floating {
+            if info.workspace_id == Some(ws_id) && info.is_tiled() {
                 if found.is_some() {
                     return None;
                 }
@@ -161,7 +315,7 @@ impl State {
             .windows
             .values()
             .filter_map(|info| {
-                if !info.is_floating {
+                if info.is_tiled() {
                     info.workspace_id
                 } else {
                     None
@@ -183,6 +337,17 @@ impl State {
     }
 }
 
+/// Window width a change gives on an output of given width.
+fn expected_width(change: SizeChange, width: i32, output_width: f64) -> i32 {
+    let proportion = |percent: f64| (output_width * percent / 100.0) as i32;
+    match change {
+        SizeChange::SetFixed(fixed) => fixed,
+        SizeChange::SetProportion(percent) => proportion(percent),
+        SizeChange::AdjustFixed(delta) => width + delta,
+        SizeChange::AdjustProportion(percent) => width + proportion(percent),
+    }
+}
+
 /// Send an action on a fresh socket connection.
 fn send_action(action: Action) {
     let mut sock = match Socket::connect() {
@@ -197,29 +362,64 @@ fn send_action(action: Action) {
     }
 }
 
-/// Toggle maximize on a window. Handles focus switching and restoring.
-fn toggle_maximize(state: &State, win_id: u64) {
+/// Queue actions working on the focused column against a window. Handles
+/// focus switching and restoring.
+fn act_on_window(state: &mut State, win_id: u64, actions: Vec<Action>) {
     let need_refocus = state.focused_id != Some(win_id);
     let restore_to = state.focused_id;
 
     if need_refocus {
-        send_action(Action::FocusWindow { id: win_id });
+        state.actions.push(Action::FocusWindow { id: win_id });
     }
 
-    send_action(Action::MaximizeColumn {});
+    state.actions.extend(actions);
 
     if need_refocus {
         if let Some(restore_id) = restore_to {
             if state.windows.contains_key(&restore_id) {
-                send_action(Action::FocusWindow { id: restore_id });
+                state.actions.push(Action::FocusWindow { id: restore_id });
             }
         }
     }
 }
 
-/// Compare previous and current tiled counts, act on transitions.
-/// `moved_window` is Some((win_id, old_workspace_id)) if a window just changed workspaces.
-fn reconcile(state: &mut State, moved_window: Option<(u64, Option<u64>)>) {
+/// Set a window's column width, the width niri reports back is what we expect
+/// from then on.
+fn resize(state: &mut State, win_id: u64, change: SizeChange) {
+    state.start_resize(win_id, change);
+    state.actions.push(Action::SetWindowWidth {
+        id: Some(win_id),
+        change,
+    });
+}
+
+/// Make a window's column full width, remembering the width to restore.
+fn maximize(state: &mut State, win_id: u64) {
+    state.remember_width(win_id);
+    resize(state, win_id, SizeChange::SetProportion(100.0));
+}
+
+/// Resize a window's column and center it, remembering the width to restore.
+fn center(state: &mut State, win_id: u64, change: SizeChange) {
+    state.remember_width(win_id);
+    state.start_resize(win_id, change);
+    act_on_window(
+        state,
+        win_id,
+        vec![Action::SetColumnWidth { change }, Action::CenterColumn {}],
+    );
+}
+
+/// Put back the width a window had before we maximized or centered it.
+fn restore(state: &mut State, win_id: u64) {
+    if let Some(width) = state.restore.remove(&win_id) {
+        resize(state, win_id, SizeChange::SetFixed(width));
+    }
+}
+
+/// Compare previous and current tiled counts, act on transitions. A window
+/// moved between workspaces shows up as a transition on both of them.
+fn reconcile(state: &mut State) {
     let current_counts = state.snapshot_counts();
 
     // Collect all workspace IDs from both old and new counts.
@@ -237,44 +437,51 @@ fn reconcile(state: &mut State, moved_window: Option<(u64, Option<u64>)>) {
         if prev == curr {
             continue;
         }
+        let Some(widths) = state.widths(ws_id) else {
+            continue;
+        };
+        let preset = config::preset(widths, curr);
+
+        if curr > 1 {
+            // No longer alone: put back windows we maximized or centered, unless
+            // a preset sets their width anyway.
+            for win_id in state.tiled_windows(ws_id) {
+                if matches!(preset, Some(Preset::Width(_))) {
+                    state.restore.remove(&win_id);
+                } else {
+                    restore(state, win_id);
+                }
+            }
+        }
 
         if curr == 1 {
-            // Transitioned to exactly 1 tiled window: maximize it if not already.
-            if let Some(win_id) = state.sole_tiled_window(ws_id) {
-                if !state.is_maximized(win_id) {
-                    toggle_maximize(state, win_id);
-                }
+            let Some(win_id) = state.sole_tiled_window(ws_id) else {
+                continue;
+            };
+            if state.manual.contains(&win_id) {
+                continue;
             }
-        } else if prev == 1 && curr > 1 {
-            // Went from 1 to many: unmaximize the previously-sole window if it's maximized.
-            if let Some(old_win_id) = state.sole_window.get(&ws_id).copied() {
-                if state.windows.contains_key(&old_win_id) && state.is_maximized(old_win_id) {
-                    toggle_maximize(state, old_win_id);
-                }
+            // Sole window on a wide output: center it instead of the preset.
+            if let Some(change) = state.center_width(ws_id) {
+                center(state, win_id, change);
+                continue;
             }
         }
-    }
 
-    // If a window moved to a non-empty workspace and it's maximized, unmaximize it.
-    if let Some((win_id, _old_ws)) = moved_window {
-        if let Some(info) = state.windows.get(&win_id) {
-            if let Some(new_ws) = info.workspace_id {
-                let count = current_counts.get(&new_ws).copied().unwrap_or(0);
-                if count > 1 && state.is_maximized(win_id) {
-                    toggle_maximize(state, win_id);
+        match preset {
+            Some(Preset::Maximize) => {
+                if let Some(win_id) = state.sole_tiled_window(ws_id) {
+                    maximize(state, win_id);
                 }
             }
-        }
-    }
-
-    // Update sole_window tracking.
-    for (&ws_id, &count) in &current_counts {
-        if count == 1 {
-            if let Some(win_id) = state.sole_tiled_window(ws_id) {
-                state.sole_window.insert(ws_id, win_id);
+            Some(Preset::Width(change)) => {
+                for win_id in state.tiled_windows(ws_id) {
+                    if !state.manual.contains(&win_id) {
+                        resize(state, win_id, change);
+                    }
+                }
             }
-        } else {
-            state.sole_window.remove(&ws_id);
+            None => {}
         }
     }
 
@@ -283,8 +490,7 @@ fn reconcile(state: &mut State, moved_window: Option<(u64, Option<u64>)>) {
 
 /// Fetch outputs from niri.
 fn fetch_outputs() -> HashMap<String, Output> {
-    let mut sock = Socket::connect().expect("Failed to connect to niri socket");
-    match sock.send(Request::Outputs) {
+    match request(Request::Outputs) {
         Ok(Ok(Response::Outputs(outputs))) => outputs,
         other => {
             eprintln!("Failed to fetch outputs: {other:?}");
@@ -295,8 +501,7 @@ fn fetch_outputs() -> HashMap<String, Output> {
 
 /// Fetch workspaces from niri.
 fn fetch_workspaces() -> Vec<Workspace> {
-    let mut sock = Socket::connect().expect("Failed to connect to niri socket");
-    match sock.send(Request::Workspaces) {
+    match request(Request::Workspaces) {
         Ok(Ok(Response::Workspaces(ws))) => ws,
         other => {
             eprintln!("Failed to fetch workspaces: {other:?}");
@@ -305,80 +510,207 @@ fn fetch_workspaces() -> Vec<Workspace> {
     }
 }
 
-fn main() {
-    // Fetch initial output and workspace info.
-    let outputs = fetch_outputs();
-    let workspaces = fetch_workspaces();
-
-    let mut state = State::new();
-    state.update_outputs(&outputs);
-    state.update_workspaces(&workspaces);
-
-    // Connect event stream.
-    let mut event_socket = Socket::connect().expect("Failed to connect to niri socket");
-    let reply = event_socket
-        .send(Request::EventStream)
-        .expect("Failed to request event stream");
-
-    match reply {
-        Ok(Response::Handled) => {}
+/// Fetch windows from niri.
+fn fetch_windows() -> Vec<Window> {
+    match request(Request::Windows) {
+        Ok(Ok(Response::Windows(windows))) => windows,
         other => {
-            eprintln!("Unexpected reply to EventStream: {other:?}");
-            std::process::exit(1);
+            eprintln!("Failed to fetch windows: {other:?}");
+            vec![]
         }
     }
+}
 
-    let mut read_event = event_socket.read_events();
+/// Send a request on a fresh socket connection.
+fn request(request: Request) -> io::Result<Reply> {
+    Socket::connect()?.send(request)
+}
 
-    loop {
-        let event = match read_event() {
-            Ok(e) => e,
-            Err(e) => {
-                eprintln!("Event stream error: {e}");
-                break;
-            }
-        };
+/// Refetch everything, niri may have changed anything while we were away.
+/// What we know about windows still open is kept.
+fn resync(state: &mut State) {
+    state.update_outputs(&fetch_outputs());
+    state.update_workspaces(&fetch_workspaces());
+    state.sync_windows(&fetch_windows());
+}
 
-        match event {
-            Event::WindowsChanged { windows } => {
-                state.reset_windows(&windows);
-                // On full reset, set prev counts to current so we don't
-                // spuriously act on startup state.
-                state.prev_tiled_count = state.snapshot_counts();
-                // Track sole windows for each workspace.
-                for (&ws_id, &count) in &state.prev_tiled_count {
-                    if count == 1 {
-                        if let Some(win_id) = state.sole_tiled_window(ws_id) {
-                            state.sole_window.insert(ws_id, win_id);
-                        }
-                    }
-                }
-            }
-            Event::WindowOpenedOrChanged { window } => {
-                let moved = state.upsert_window(&window);
-                reconcile(&mut state, moved);
-            }
-            Event::WindowClosed { id } => {
-                state.remove_window(id);
-                reconcile(&mut state, None);
-            }
-            Event::WindowFocusChanged { id } => {
-                state.focused_id = id;
-            }
-            Event::WorkspacesChanged { workspaces } => {
-                state.update_workspaces(&workspaces);
-                let outputs = fetch_outputs();
-                state.update_outputs(&outputs);
+fn connect_event_stream() -> io::Result<Socket> {
+    let mut socket = Socket::connect()?;
+    match socket.send(Request::EventStream)? {
+        Ok(Response::Handled) => Ok(socket),
+        other => Err(io::Error::other(format!(
+            "Unexpected reply to EventStream: {other:?}"
+        ))),
+    }
+}
+
+fn handle_event(state: &mut State, event: Event) {
+    match event {
+        Event::WindowsChanged { windows } => state.sync_windows(&windows),
+        Event::WindowOpenedOrChanged { window } => {
+            state.upsert_window(&window);
+            reconcile(state);
+        }
+        Event::WindowClosed { id } => {
+            state.remove_window(id);
+            reconcile(state);
+        }
+        Event::WindowFocusChanged { id } => {
+            state.focused_id = id;
+        }
+        Event::WorkspacesChanged { workspaces } => {
+            state.update_workspaces(&workspaces);
+            let outputs = fetch_outputs();
+            state.update_outputs(&outputs);
+        }
+        Event::WindowLayoutsChanged { changes } => {
+            for (win_id, layout) in &changes {
+                state.update_layout(*win_id, layout);
             }
-            Event::WindowLayoutsChanged { changes } => {
-                // Keep tile widths up to date for is_maximized checks.
-                for (win_id, layout) in &changes {
-                    if let Some(info) = state.windows.get_mut(win_id) {
-                        info.tile_width = layout.tile_size.0;
-                    }
+        }
+        _ => {}
+    }
+}
+
+fn main() {
+    let config = Rc::new(Config::load());
+    let mut event_socket = connect_event_stream().unwrap_or_else(|e| {
+        eprintln!("Failed to connect to niri event stream: {e}");
+        std::process::exit(1);
+    });
+
+    let mut backoff = Backoff::new();
+    let mut state = State::new(config);
+
+    loop {
+        resync(&mut state);
+        let mut read_event = event_socket.read_events();
+
+        loop {
+            let event = match read_event() {
+                Ok(e) => e,
+                Err(e) => {
+                    eprintln!("Event stream error: {e}");
+                    break;
                 }
+            };
+            backoff.reset();
+            handle_event(&mut state, event);
+            for action in state.actions.drain(..) {
+                send_action(action);
             }
-            _ => {}
         }
+
+        event_socket = backoff.retry(connect_event_stream);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Workspace 1 on a 2000 wide output, 2 on a 1000 wide one.
+    fn state() -> State {
+        let mut state = State::new(Rc::new(Config::default()));
+        state.workspace_output.insert(1, "DP-1".to_string());
+        state.workspace_output.insert(2, "HDMI-A-1".to_string());
+        state.output_width.insert("DP-1".to_string(), 2000.0);
+        state.output_width.insert("HDMI-A-1".to_string(), 1000.0);
+        state
+    }
+
+    fn layout(tile_width: f64, window_width: i32) -> WindowLayout {
+        WindowLayout {
+            pos_in_scrolling_layout: Some((1, 1)),
+            tile_size: (tile_width, 1000.0),
+            window_size: (window_width, 1000),
+            tile_pos_in_workspace_view: None,
+            window_offset_in_tile: (0.0, 0.0),
+        }
+    }
+
+    fn open(state: &mut State, workspace_id: u64, window_width: i32) {
+        let window = Window {
+            id: 1,
+            title: None,
+            app_id: None,
+            pid: None,
+            workspace_id: Some(workspace_id),
+            is_focused: true,
+            is_floating: false,
+            is_urgent: false,
+            layout: layout(window_width as f64 + 8.0, window_width),
+            focus_timestamp: None,
+        };
+        handle_event(state, Event::WindowOpenedOrChanged { window });
+    }
+
+    /// Actions queued since the last call.
+    fn sent(state: &mut State) -> Vec<String> {
+        state
+            .actions
+            .drain(..)
+            .map(|action| format!("{action:?}"))
+            .collect()
+    }
+
+    fn report(state: &mut State, tile_width: f64, window_width: i32) {
+        let changes = vec![(1, layout(tile_width, window_width))];
+        handle_event(state, Event::WindowLayoutsChanged { changes });
+    }
+
+    #[test]
+    fn test_user_resize_after_ours_is_manual() {
+        let mut state = state();
+        open(&mut state, 1, 800);
+        assert_eq!(state.restore.get(&1), Some(&800));
+        assert_eq!(
+            sent(&mut state),
+            vec!["SetWindowWidth { id: Some(1), change: SetProportion(100.0) }"]
+        );
+
+        // stale layout, then ours
+        report(&mut state, 808.0, 800);
+        report(&mut state, 1976.0, 1968);
+        assert!(!state.manual.contains(&1));
+        assert_eq!(state.restore.get(&1), Some(&800));
+
+        report(&mut state, 1208.0, 1200);
+        assert!(state.manual.contains(&1));
+        assert_eq!(state.restore.get(&1), None);
+    }
+
+    #[test]
+    fn test_user_resize_after_no_op_resize_is_manual() {
+        let mut state = state();
+        open(&mut state, 1, 1968);
+
+        report(&mut state, 1208.0, 1200);
+        assert!(state.manual.contains(&1));
+    }
+
+    #[test]
+    fn test_fullscreen_and_workspace_moves_are_not_manual() {
+        let mut state = state();
+        open(&mut state, 1, 800);
+        report(&mut state, 1976.0, 1968);
+
+        report(&mut state, 2000.0, 2000);
+        report(&mut state, 1976.0, 1968);
+        assert!(!state.manual.contains(&1));
+
+        // the old width shows up on the narrower output before ours
+        sent(&mut state);
+        open(&mut state, 2, 1968);
+        assert_eq!(
+            sent(&mut state),
+            vec!["SetWindowWidth { id: Some(1), change: SetProportion(100.0) }"]
+        );
+        report(&mut state, 1976.0, 1968);
+        report(&mut state, 992.0, 984);
+        assert!(!state.manual.contains(&1));
+
+        report(&mut state, 608.0, 600);
+        assert!(state.manual.contains(&1));
     }
 }
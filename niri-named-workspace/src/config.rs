@@ -0,0 +1,85 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Named workspaces, read from
+/// `$XDG_CONFIG_HOME/i-use-rust-btw/niri-named-workspace.toml`:
+///
+/// ```toml
+/// [workspaces.mail]
+/// output = "eDP-1"
+/// index = 1
+/// column-width = "50%"
+/// # spawned when the workspace is focused and empty
+/// spawn = ["thunderbird"]
+/// ```
+///
+/// Widths are applied to spawned and moved windows. Command line options win
+/// over the config.
+#[derive(Default, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub workspaces: HashMap<String, Spec>,
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Spec {
+    pub output: Option<String>,
+    pub index: Option<usize>,
+    pub column_width: Option<String>,
+    pub window_width: Option<String>,
+    /// Shell commands.
+    #[serde(default)]
+    pub spawn: Vec<String>,
+}
+
+impl Config {
+    /// Load config from `path` or the default location. Missing file means
+    /// defaults, broken one is reported and ends the process.
+    pub fn load(path: Option<&str>) -> Config {
+        let path = match path {
+            Some(path) => path.to_string(),
+            None => default_config_path(),
+        };
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return Config::default(),
+        };
+        toml::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("Can not parse config {path}: {e}");
+            std::process::exit(1);
+        })
+    }
+
+    /// Spec of the workspace, empty one if it is not configured.
+    pub fn spec(&self, name: &str) -> Spec {
+        self.workspaces.get(name).cloned().unwrap_or_default()
+    }
+}
+
+fn default_config_path() -> String {
+    let dir = std::env::var("XDG_CONFIG_HOME").unwrap_or_else(|_| {
+        let home = std::env::var("HOME").unwrap_or("/tmp".into());
+        format!("{home}/.config")
+    });
+    format!("{dir}/i-use-rust-btw/niri-named-workspace.toml")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_spec_from_toml() {
+        let config: Config = toml::from_str(
+            "[workspaces.mail]\noutput = \"eDP-1\"\nindex = 1\ncolumn-width = \"50%\"\nspawn = [\"thunderbird\"]\n",
+        )
+        .unwrap();
+        let mail = config.spec("mail");
+        assert_eq!(mail.output.as_deref(), Some("eDP-1"));
+        assert_eq!(mail.index, Some(1));
+        assert_eq!(mail.column_width.as_deref(), Some("50%"));
+        assert_eq!(mail.spawn, vec!["thunderbird"]);
+        assert!(config.spec("chat").spawn.is_empty());
+    }
+}
@@ -1,66 +1,287 @@
-use clap::{arg, Command};
+use clap::{arg, Arg, ArgAction, ArgMatches, Command};
+use config::{Config, Spec};
 use niri_ipc::socket::Socket;
-use niri_ipc::{Action, Request, Response, SizeChange, Workspace, WorkspaceReferenceArg};
+use niri_ipc::{Action, Request, Response, SizeChange, Window, Workspace, WorkspaceReferenceArg};
+use regex::Regex;
+use serde::Serialize;
+use std::io;
+use std::time::Duration;
+
+mod config;
+
+/// How long to wait for spawned windows, 5s in total.
+const SPAWN_POLLS: u32 = 50;
+const SPAWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Arguments of the subcommands working with a single named workspace.
+fn workspace_args() -> Vec<Arg> {
+    vec![
+        arg!(<NAME> "Name of the workspace"),
+        arg!(-o --"output" <OUTPUT> "Create named workspace on this output instead of the focused one"),
+        arg!(-i --"index" <INDEX> "Move newly created named workspace to this index on its output")
+            .value_parser(clap::value_parser!(usize)),
+        arg!(-c --"column-width" <SIZE> "Set column width after moving (e.g. '50%', '800', '+10%')"),
+        arg!(-w --"window-width" <SIZE> "Set window width after moving (e.g. '50%', '800', '+10%')"),
+    ]
+}
 
 fn cli() -> Command {
     Command::new("niri-named-workspace")
         .about("Focus or move window to a named workspace in niri")
-        .args(vec![
-            arg!(<ACTION> "Action to perform: 'focus' or 'move'"),
-            arg!(<NAME> "Name of the workspace"),
-            arg!(-o --"output" <OUTPUT> "Create named workspace on this output instead of the focused one"),
-            arg!(-c --"column-width" <SIZE> "Set column width after moving (e.g. '50%', '800', '+10%')"),
-            arg!(-w --"window-width" <SIZE> "Set window width after moving (e.g. '50%', '800', '+10%')"),
+        .after_help(
+            "Operations can be chained with --then, e.g. `focus mail --then spawn thunderbird`",
+        )
+        .subcommand_required(true)
+        .arg(arg!(--"config" <FILE> "Config file with workspace specs").global(true))
+        .subcommands([
+            Command::new("focus")
+                .about("Focus named workspace, creating it when needed")
+                .args(workspace_args()),
+            Command::new("toggle")
+                .about("Focus named workspace, or go back when it is focused already")
+                .args(workspace_args()),
+            Command::new("move")
+                .about("Move focused window to named workspace")
+                .args(workspace_args()),
+            Command::new("move-column")
+                .about("Move focused column to named workspace")
+                .args(workspace_args()),
+            Command::new("move-app")
+                .about("Move all windows of an app to named workspace")
+                .args(workspace_args())
+                .arg(arg!(<APP> "App id regex of windows to move")),
+            Command::new("unname")
+                .about("Turn named workspace back into a dynamic one")
+                .arg(arg!(<NAME> "Name of the workspace")),
+            Command::new("list").about("Print named workspaces as JSON"),
+            Command::new("spawn")
+                .about("Spawn a command, mostly useful after --then")
+                .arg(
+                    Arg::new("COMMAND")
+                        .help("Command with its arguments")
+                        .required(true)
+                        .num_args(1..)
+                        .trailing_var_arg(true)
+                        .allow_hyphen_values(true),
+                ),
+            Command::new("gc")
+                .about("Unname empty named workspaces, except configured and kept ones")
+                .arg(
+                    arg!(-k --"keep" <NAME> "Workspace to leave named even when empty")
+                        .action(ArgAction::Append),
+                ),
         ])
 }
 
-fn main() {
-    let matches = cli().get_matches();
-
-    let action = matches
-        .get_one::<String>("ACTION")
-        .expect("Action is required");
-    let name = matches.get_one::<String>("NAME").expect("Name is required");
-    let output = matches.get_one::<String>("output").cloned();
-    let column_width = matches.get_one::<String>("column-width").map(|s| {
-        s.parse::<SizeChange>().unwrap_or_else(|e| {
-            eprintln!("Invalid --column-width value: {e}");
-            std::process::exit(1);
-        })
-    });
-    let window_width = matches.get_one::<String>("window-width").map(|s| {
-        s.parse::<SizeChange>().unwrap_or_else(|e| {
-            eprintln!("Invalid --window-width value: {e}");
-            std::process::exit(1);
-        })
-    });
+/// Named workspace to work with and how.
+struct Named {
+    name: String,
+    /// Config spec with command line options applied.
+    spec: Spec,
+    column_width: Option<SizeChange>,
+    window_width: Option<SizeChange>,
+    /// Width given on the command line, focus resizes the focused column
+    /// with it even when nothing is spawned.
+    resize_focused: bool,
+}
 
-    match action.as_str() {
-        "focus" => focus_workspace(name, output.as_deref(), column_width, window_width),
-        "move" => move_to_workspace(name, output.as_deref(), column_width, window_width),
-        other => {
-            eprintln!("Unknown action: {other}. Use 'focus' or 'move'.");
-            std::process::exit(1);
+impl Named {
+    fn from_matches(matches: &ArgMatches, config: &Config) -> Named {
+        let name = matches.get_one::<String>("NAME").expect("Name is required");
+        let mut spec = config.spec(name);
+        if let Some(output) = matches.get_one::<String>("output") {
+            spec.output = Some(output.clone());
+        }
+        if let Some(index) = matches.get_one::<usize>("index") {
+            spec.index = Some(*index);
         }
+        let mut resize_focused = false;
+        if let Some(width) = matches.get_one::<String>("column-width") {
+            spec.column_width = Some(width.clone());
+            resize_focused = true;
+        }
+        if let Some(width) = matches.get_one::<String>("window-width") {
+            spec.window_width = Some(width.clone());
+            resize_focused = true;
+        }
+        // Fail early on bad sizes, before anything is changed.
+        let column_width = spec
+            .column_width
+            .as_deref()
+            .map(|s| parse_size(s, "column width"));
+        let window_width = spec
+            .window_width
+            .as_deref()
+            .map(|s| parse_size(s, "window width"));
+
+        Named {
+            name: name.clone(),
+            spec,
+            column_width,
+            window_width,
+            resize_focused,
+        }
+    }
+
+    fn has_width(&self) -> bool {
+        self.column_width.is_some() || self.window_width.is_some()
     }
 }
 
-fn fetch_workspaces() -> Vec<Workspace> {
-    let mut socket = Socket::connect().expect("Failed to connect to niri socket");
-    match socket.send(Request::Workspaces) {
-        Ok(Ok(Response::Workspaces(ws))) => ws,
-        other => {
-            eprintln!("Failed to fetch workspaces: {other:?}");
-            vec![]
+enum Op {
+    Focus(Named),
+    Toggle(Named),
+    Move(Named),
+    MoveColumn(Named),
+    MoveApp(Named, Regex),
+    Unname(String),
+    List,
+    /// Names to keep.
+    Gc(Vec<String>),
+    Spawn(Vec<String>),
+}
+
+impl Op {
+    fn from_matches(matches: &ArgMatches, config: &Config) -> Op {
+        let (command, matches) = matches.subcommand().expect("Subcommand is required");
+        match command {
+            "focus" => Op::Focus(Named::from_matches(matches, config)),
+            "toggle" => Op::Toggle(Named::from_matches(matches, config)),
+            "move" => Op::Move(Named::from_matches(matches, config)),
+            "move-column" => Op::MoveColumn(Named::from_matches(matches, config)),
+            "move-app" => {
+                let app = matches.get_one::<String>("APP").expect("App is required");
+                let app = Regex::new(app).unwrap_or_else(|e| {
+                    eprintln!("Invalid app id regex: {e}");
+                    std::process::exit(1);
+                });
+                Op::MoveApp(Named::from_matches(matches, config), app)
+            }
+            "unname" => Op::Unname(matches.get_one::<String>("NAME").unwrap().clone()),
+            "list" => Op::List,
+            "gc" => {
+                let mut keep: Vec<String> = config.workspaces.keys().cloned().collect();
+                if let Some(names) = matches.get_many::<String>("keep") {
+                    keep.extend(names.cloned());
+                }
+                Op::Gc(keep)
+            }
+            "spawn" => Op::Spawn(
+                matches
+                    .get_many::<String>("COMMAND")
+                    .unwrap()
+                    .cloned()
+                    .collect(),
+            ),
+            _ => unreachable!("Unknown subcommand {command}"),
+        }
+    }
+
+    fn run(&self, niri: &mut Niri) -> io::Result<()> {
+        match self {
+            Op::Focus(named) => focus_workspace(niri, named),
+            Op::Toggle(named) => toggle_workspace(niri, named),
+            Op::Move(named) => move_to_workspace(niri, named),
+            Op::MoveColumn(named) => move_column_to_workspace(niri, named),
+            Op::MoveApp(named, app) => move_app_to_workspace(niri, named, app),
+            Op::Unname(name) => unname_workspace(niri, name),
+            Op::List => list_workspaces(niri),
+            Op::Gc(keep) => gc_workspaces(niri, keep),
+            Op::Spawn(command) => niri.send_action(Action::Spawn {
+                command: command.clone(),
+            }),
         }
     }
 }
 
-fn send_action(action: Action) {
-    let mut socket = Socket::connect().expect("Failed to connect to niri socket");
-    if let Err(e) = socket.send(Request::Action(action)) {
-        eprintln!("Failed to send action: {e}");
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let ops = parse(&args);
+
+    let result =
+        Niri::connect().and_then(|mut niri| ops.iter().try_for_each(|op| op.run(&mut niri)));
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+/// Parses every operation first, so a typo in a later one does not leave the
+/// earlier ones done. Global options are given once, before the first one.
+fn parse(args: &[String]) -> Vec<Op> {
+    let (program, args) = args.split_first().expect("Program name is always given");
+    let segments: Vec<ArgMatches> = args
+        .split(|arg| arg == "--then")
+        .map(|args| cli().get_matches_from(std::iter::once(program).chain(args)))
+        .collect();
+
+    if segments[1..].iter().any(|m| m.contains_id("config")) {
+        eprintln!("--config applies to all operations, give it before the first one");
+        std::process::exit(1);
+    }
+    let config = Config::load(segments[0].get_one::<String>("config").map(|c| c.as_str()));
+
+    segments
+        .iter()
+        .map(|matches| Op::from_matches(matches, &config))
+        .collect()
+}
+
+fn parse_size(value: &str, what: &str) -> SizeChange {
+    value.parse::<SizeChange>().unwrap_or_else(|e| {
+        eprintln!("Invalid {what} value: {e}");
+        std::process::exit(1);
+    })
+}
+
+/// Connection to niri shared by everything done in one invocation.
+struct Niri {
+    socket: Socket,
+}
+
+impl Niri {
+    fn connect() -> io::Result<Niri> {
+        Ok(Niri {
+            socket: Socket::connect()?,
+        })
+    }
+
+    /// Sends the request, niri's error reply is returned as an error.
+    fn request(&mut self, request: Request) -> io::Result<Response> {
+        self.socket.send(request)?.map_err(io::Error::other)
     }
+
+    fn fetch_workspaces(&mut self) -> io::Result<Vec<Workspace>> {
+        match self.request(Request::Workspaces)? {
+            Response::Workspaces(workspaces) => Ok(workspaces),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    fn fetch_windows(&mut self) -> io::Result<Vec<Window>> {
+        match self.request(Request::Windows)? {
+            Response::Windows(windows) => Ok(windows),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    fn fetch_focused_window_id(&mut self) -> io::Result<Option<u64>> {
+        match self.request(Request::FocusedWindow)? {
+            Response::FocusedWindow(window) => Ok(window.map(|w| w.id)),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    fn send_action(&mut self, action: Action) -> io::Result<()> {
+        match self.request(Request::Action(action))? {
+            Response::Handled => Ok(()),
+            other => Err(unexpected(other)),
+        }
+    }
+}
+
+fn unexpected(response: Response) -> io::Error {
+    io::Error::other(format!("Unexpected reply: {response:?}"))
 }
 
 /// Find the output of the currently focused workspace.
@@ -90,85 +311,487 @@ fn find_empty_unnamed_workspace<'a>(
         .find(|ws| ws.active_window_id.is_none())
 }
 
+/// Index of the trailing dynamic workspace niri keeps at the bottom of the
+/// output, one past the last workspace when that one is used or named. An
+/// index past the last workspace lands on the last one.
+fn trailing_index(workspaces: &[Workspace], output: &str) -> u8 {
+    let last = workspaces
+        .iter()
+        .filter(|ws| ws.output.as_deref() == Some(output))
+        .max_by_key(|ws| ws.idx);
+    match last {
+        Some(ws) if ws.name.is_none() && ws.active_window_id.is_none() => ws.idx,
+        Some(ws) => ws.idx.saturating_add(1),
+        None => 1,
+    }
+}
+
+/// Get to the trailing dynamic workspace by focusing the output and its last
+/// index. Returns the workspace if it ended up focused and is empty and
+/// unnamed, otherwise the previously focused workspace is focused again.
+fn focus_trailing_workspace(
+    niri: &mut Niri,
+    workspaces: &[Workspace],
+    output: &str,
+) -> io::Result<Option<u64>> {
+    let previous = workspaces.iter().find(|ws| ws.is_focused).map(|ws| ws.id);
+    let idx = trailing_index(workspaces, output);
+
+    let trailing = niri
+        .send_action(Action::FocusMonitor {
+            output: output.to_string(),
+        })
+        .and_then(|_| {
+            niri.send_action(Action::FocusWorkspace {
+                reference: WorkspaceReferenceArg::Index(idx),
+            })
+        })
+        .and_then(|_| niri.fetch_workspaces())
+        .map(|workspaces| {
+            workspaces
+                .iter()
+                .find(|ws| ws.is_focused && ws.output.as_deref() == Some(output))
+                .filter(|ws| ws.name.is_none() && ws.active_window_id.is_none())
+                .map(|ws| ws.id)
+        });
+
+    if !matches!(trailing, Ok(Some(_))) {
+        if let Some(id) = previous {
+            niri.send_action(Action::FocusWorkspace {
+                reference: WorkspaceReferenceArg::Id(id),
+            })?;
+        }
+    }
+    trailing
+}
+
 /// Ensure a workspace with the given name exists. If it doesn't, name the first
-/// empty unnamed workspace on the specified (or focused) output. Returns true if
-/// the workspace exists (or was created), false otherwise.
-fn ensure_named_workspace(name: &str, output: Option<&str>) -> bool {
-    let workspaces = fetch_workspaces();
+/// empty unnamed workspace on the specified (or focused) output, falling back to
+/// the trailing one, and move it to `index` if given.
+fn ensure_named_workspace(
+    niri: &mut Niri,
+    name: &str,
+    output: Option<&str>,
+    index: Option<usize>,
+) -> io::Result<()> {
+    let workspaces = niri.fetch_workspaces()?;
 
     if let Some(ws) = find_workspace_by_name(&workspaces, name) {
         println!("{}", ws.id);
-        return true;
+        return Ok(());
     }
 
     let target_output = match output {
         Some(o) => o.to_string(),
-        None => match focused_output(&workspaces) {
-            Some(o) => o,
-            None => {
-                eprintln!("No focused output found");
-                return false;
-            }
-        },
+        None => focused_output(&workspaces)
+            .ok_or_else(|| io::Error::other("No focused output found"))?,
     };
 
-    match find_empty_unnamed_workspace(&workspaces, &target_output) {
-        Some(ws) => {
-            send_action(Action::SetWorkspaceName {
-                name: name.to_string(),
-                workspace: Some(WorkspaceReferenceArg::Id(ws.id)),
-            });
-            println!("{}", ws.id);
-            true
+    let id = match find_empty_unnamed_workspace(&workspaces, &target_output) {
+        Some(ws) => ws.id,
+        None => focus_trailing_workspace(niri, &workspaces, &target_output)?.ok_or_else(|| {
+            io::Error::other(format!(
+                "No empty unnamed workspace available on output {target_output}"
+            ))
+        })?,
+    };
+    niri.send_action(Action::SetWorkspaceName {
+        name: name.to_string(),
+        workspace: Some(WorkspaceReferenceArg::Id(id)),
+    })?;
+    if let Some(index) = index {
+        niri.send_action(Action::MoveWorkspaceToIndex {
+            index,
+            reference: Some(WorkspaceReferenceArg::Id(id)),
+        })?;
+    }
+    println!("{id}");
+    Ok(())
+}
+
+/// File keeping the workspace focused before the last focus / toggle.
+fn previous_workspace_path() -> String {
+    let dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or("/tmp".into());
+    format!("{dir}/niri_named_workspace_previous")
+}
+
+fn read_previous_workspace() -> Option<u64> {
+    std::fs::read_to_string(previous_workspace_path())
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn remember_previous_workspace(id: u64) {
+    if let Err(e) = std::fs::write(previous_workspace_path(), id.to_string()) {
+        eprintln!("Failed to remember previous workspace: {e}");
+    }
+}
+
+/// Resizes the window and its column, the column is only reachable through
+/// focus.
+fn resize_window(niri: &mut Niri, named: &Named, id: u64) -> io::Result<()> {
+    if let Some(change) = named.column_width {
+        niri.send_action(Action::FocusWindow { id })?;
+        niri.send_action(Action::SetColumnWidth { change })?;
+    }
+    if let Some(change) = named.window_width {
+        niri.send_action(Action::SetWindowWidth {
+            id: Some(id),
+            change,
+        })?;
+    }
+    Ok(())
+}
+
+fn resize_focused(niri: &mut Niri, named: &Named) -> io::Result<()> {
+    if let Some(change) = named.column_width {
+        niri.send_action(Action::SetColumnWidth { change })?;
+    }
+    if let Some(change) = named.window_width {
+        niri.send_action(Action::SetWindowWidth { id: None, change })?;
+    }
+    Ok(())
+}
+
+/// Windows on the workspace that were not there before.
+fn new_windows(windows: &[Window], before: &[u64], workspace_id: u64) -> Vec<u64> {
+    windows
+        .iter()
+        .filter(|w| w.workspace_id == Some(workspace_id) && !before.contains(&w.id))
+        .map(|w| w.id)
+        .collect()
+}
+
+/// Spawning is asynchronous, so waits for a window per command to show up on
+/// the workspace and resizes each as it comes.
+fn resize_spawned(
+    niri: &mut Niri,
+    named: &Named,
+    workspace_id: u64,
+    before: &[u64],
+) -> io::Result<()> {
+    let mut resized: Vec<u64> = vec![];
+    for _ in 0..SPAWN_POLLS {
+        std::thread::sleep(SPAWN_POLL_INTERVAL);
+        for id in new_windows(&niri.fetch_windows()?, before, workspace_id) {
+            if !resized.contains(&id) {
+                resize_window(niri, named, id)?;
+                resized.push(id);
+            }
         }
-        None => {
-            eprintln!("No empty unnamed workspace available on output {target_output}");
-            false
+        if resized.len() >= named.spec.spawn.len() {
+            return Ok(());
         }
     }
+    eprintln!(
+        "Spawned windows did not show up on workspace {}, not resized",
+        named.name
+    );
+    Ok(())
+}
+
+fn ensure(niri: &mut Niri, named: &Named) -> io::Result<()> {
+    ensure_named_workspace(
+        niri,
+        &named.name,
+        named.spec.output.as_deref(),
+        named.spec.index,
+    )
+}
+
+fn reference(named: &Named) -> WorkspaceReferenceArg {
+    WorkspaceReferenceArg::Name(named.name.clone())
 }
 
-fn apply_size_actions(column_width: Option<SizeChange>, window_width: Option<SizeChange>) {
-    if let Some(change) = column_width {
-        send_action(Action::SetColumnWidth { change });
+/// Focuses the workspace, spawning its apps when it is empty. Widths apply to
+/// the spawned windows, to the focused column only when given as flags.
+fn focus_workspace(niri: &mut Niri, named: &Named) -> io::Result<()> {
+    let focused = niri
+        .fetch_workspaces()?
+        .into_iter()
+        .find(|ws| ws.is_focused);
+    if let Some(ws) = focused.filter(|ws| ws.name.as_ref() != Some(&named.name)) {
+        remember_previous_workspace(ws.id);
     }
-    if let Some(change) = window_width {
-        send_action(Action::SetWindowWidth { id: None, change });
+    ensure(niri, named)?;
+
+    niri.send_action(Action::FocusWorkspace {
+        reference: reference(named),
+    })?;
+
+    let empty = find_workspace_by_name(&niri.fetch_workspaces()?, &named.name)
+        .filter(|ws| ws.active_window_id.is_none())
+        .map(|ws| ws.id);
+    match empty {
+        Some(workspace_id) if !named.spec.spawn.is_empty() => {
+            let before: Vec<u64> = niri.fetch_windows()?.iter().map(|w| w.id).collect();
+            for command in &named.spec.spawn {
+                niri.send_action(Action::SpawnSh {
+                    command: command.clone(),
+                })?;
+            }
+            if named.has_width() {
+                resize_spawned(niri, named, workspace_id, &before)?;
+            }
+            Ok(())
+        }
+        _ if named.resize_focused => resize_focused(niri, named),
+        _ => Ok(()),
     }
 }
 
-fn focus_workspace(
-    name: &str,
-    output: Option<&str>,
-    column_width: Option<SizeChange>,
-    window_width: Option<SizeChange>,
-) {
-    if !ensure_named_workspace(name, output) {
-        return;
+/// Like focus, but when the named workspace is focused already go back to the
+/// previously focused one.
+fn toggle_workspace(niri: &mut Niri, named: &Named) -> io::Result<()> {
+    let workspaces = niri.fetch_workspaces()?;
+    let focused = workspaces
+        .iter()
+        .find(|ws| ws.is_focused && ws.name.as_ref() == Some(&named.name));
+    let Some(focused) = focused else {
+        return focus_workspace(niri, named);
+    };
+
+    let id = read_previous_workspace()
+        .filter(|id| workspaces.iter().any(|ws| ws.id == *id))
+        .ok_or_else(|| io::Error::other("No previous workspace to go back to"))?;
+    remember_previous_workspace(focused.id);
+    niri.send_action(Action::FocusWorkspace {
+        reference: WorkspaceReferenceArg::Id(id),
+    })
+}
+
+fn move_to_workspace(niri: &mut Niri, named: &Named) -> io::Result<()> {
+    // Creating the workspace can move focus to another output, so remember
+    // which window is to be moved.
+    let window_id = niri.fetch_focused_window_id()?;
+    ensure(niri, named)?;
+
+    niri.send_action(Action::MoveWindowToWorkspace {
+        window_id,
+        reference: reference(named),
+        focus: true,
+    })?;
+
+    match window_id {
+        Some(id) => resize_window(niri, named, id),
+        None => Ok(()),
     }
+}
+
+fn move_column_to_workspace(niri: &mut Niri, named: &Named) -> io::Result<()> {
+    let window_id = niri.fetch_focused_window_id()?;
+    ensure(niri, named)?;
 
-    send_action(Action::FocusWorkspace {
-        reference: WorkspaceReferenceArg::Name(name.to_string()),
-    });
+    // Focus could have moved while creating the workspace, get back to the
+    // column to be moved.
+    if let Some(id) = window_id {
+        niri.send_action(Action::FocusWindow { id })?;
+    }
+    niri.send_action(Action::MoveColumnToWorkspace {
+        reference: reference(named),
+        focus: true,
+    })?;
 
-    apply_size_actions(column_width, window_width);
+    match window_id {
+        Some(id) => resize_window(niri, named, id),
+        None => Ok(()),
+    }
 }
 
-fn move_to_workspace(
-    name: &str,
-    output: Option<&str>,
-    column_width: Option<SizeChange>,
-    window_width: Option<SizeChange>,
-) {
-    if !ensure_named_workspace(name, output) {
-        return;
+/// Moves every window with matching app id to the workspace, in the order
+/// they have on their workspaces. Focus stays where it is.
+fn move_app_to_workspace(niri: &mut Niri, named: &Named, app: &Regex) -> io::Result<()> {
+    ensure(niri, named)?;
+    let target = find_workspace_by_name(&niri.fetch_workspaces()?, &named.name).map(|ws| ws.id);
+
+    let mut windows: Vec<Window> = niri
+        .fetch_windows()?
+        .into_iter()
+        .filter(|w| w.workspace_id != target)
+        .filter(|w| app.is_match(w.app_id.as_deref().unwrap_or_default()))
+        .collect();
+    windows.sort_by_key(|w| (w.workspace_id, w.layout.pos_in_scrolling_layout));
+
+    for window in windows {
+        niri.send_action(Action::MoveWindowToWorkspace {
+            window_id: Some(window.id),
+            reference: reference(named),
+            focus: false,
+        })?;
     }
+    Ok(())
+}
 
-    send_action(Action::MoveWindowToWorkspace {
-        window_id: None,
-        reference: WorkspaceReferenceArg::Name(name.to_string()),
-        focus: true,
-    });
+/// Named workspace as printed by `list`.
+#[derive(Serialize)]
+struct NamedWorkspace<'a> {
+    name: &'a str,
+    id: u64,
+    output: Option<&'a str>,
+    index: u8,
+    windows: usize,
+    focused: bool,
+}
+
+fn named_workspaces<'a>(
+    workspaces: &'a [Workspace],
+    windows: &[Window],
+) -> Vec<NamedWorkspace<'a>> {
+    workspaces
+        .iter()
+        .filter_map(|ws| {
+            Some(NamedWorkspace {
+                name: ws.name.as_deref()?,
+                id: ws.id,
+                output: ws.output.as_deref(),
+                index: ws.idx,
+                windows: windows
+                    .iter()
+                    .filter(|w| w.workspace_id == Some(ws.id))
+                    .count(),
+                focused: ws.is_focused,
+            })
+        })
+        .collect()
+}
+
+fn list_workspaces(niri: &mut Niri) -> io::Result<()> {
+    let workspaces = niri.fetch_workspaces()?;
+    let mut named = named_workspaces(&workspaces, &niri.fetch_windows()?);
+    named.sort_by(|a, b| (a.output, a.index).cmp(&(b.output, b.index)));
+    println!("{}", serde_json::to_string(&named)?);
+    Ok(())
+}
+
+fn unname_workspace(niri: &mut Niri, name: &str) -> io::Result<()> {
+    if find_workspace_by_name(&niri.fetch_workspaces()?, name).is_none() {
+        return Err(io::Error::other(format!("No workspace named {name}")));
+    }
+    niri.send_action(Action::UnsetWorkspaceName {
+        reference: Some(WorkspaceReferenceArg::Name(name.to_string())),
+    })
+}
+
+/// Empty named workspaces that are not kept and not focused, so they do not
+/// disappear right after being created.
+fn garbage<'a>(named: &'a [NamedWorkspace<'a>], keep: &[String]) -> Vec<&'a NamedWorkspace<'a>> {
+    named
+        .iter()
+        .filter(|ws| ws.windows == 0 && !ws.focused && !keep.iter().any(|k| k == ws.name))
+        .collect()
+}
+
+fn gc_workspaces(niri: &mut Niri, keep: &[String]) -> io::Result<()> {
+    let workspaces = niri.fetch_workspaces()?;
+    let named = named_workspaces(&workspaces, &niri.fetch_windows()?);
+    for ws in garbage(&named, keep) {
+        niri.send_action(Action::UnsetWorkspaceName {
+            reference: Some(WorkspaceReferenceArg::Id(ws.id)),
+        })?;
+        println!("{}", ws.name);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn named(name: &str, windows: usize, focused: bool) -> NamedWorkspace<'_> {
+        NamedWorkspace {
+            name,
+            id: 1,
+            output: Some("eDP-1"),
+            index: 1,
+            windows,
+            focused,
+        }
+    }
+
+    fn named_from(argv: &[&str], config: &Config) -> Named {
+        let matches = cli().get_matches_from([&["niri-named-workspace", "focus"], argv].concat());
+        let (_, matches) = matches.subcommand().unwrap();
+        Named::from_matches(matches, config)
+    }
+
+    #[test]
+    fn test_only_flag_widths_resize_focused() {
+        let config: Config = toml::from_str("[workspaces.mail]\ncolumn-width = \"50%\"\n").unwrap();
+
+        let mail = named_from(&["mail"], &config);
+        assert_eq!(mail.column_width, Some(SizeChange::SetProportion(50.0)));
+        assert!(!mail.resize_focused);
+
+        let mail = named_from(&["mail", "-w", "800"], &config);
+        assert_eq!(mail.window_width, Some(SizeChange::SetFixed(800)));
+        assert!(mail.resize_focused);
+    }
 
-    apply_size_actions(column_width, window_width);
+    #[test]
+    fn test_config_applies_to_every_operation() {
+        let path = std::env::temp_dir().join("niri-named-workspace-test.toml");
+        std::fs::write(&path, "[workspaces.chat]\noutput = \"HDMI-A-1\"\n").unwrap();
+        let args: Vec<String> = [
+            "niri-named-workspace",
+            "--config",
+            path.to_str().unwrap(),
+            "focus",
+            "mail",
+            "--then",
+            "toggle",
+            "chat",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+        let ops = parse(&args);
+        let Op::Toggle(chat) = &ops[1] else {
+            panic!("toggle expected");
+        };
+        assert_eq!(chat.spec.output.as_deref(), Some("HDMI-A-1"));
+    }
+
+    fn workspace(idx: u8, output: &str, name: Option<&str>, window: Option<u64>) -> Workspace {
+        Workspace {
+            id: idx as u64,
+            idx,
+            name: name.map(String::from),
+            output: Some(output.to_string()),
+            is_urgent: false,
+            is_active: false,
+            is_focused: false,
+            active_window_id: window,
+        }
+    }
+
+    #[test]
+    fn test_trailing_workspace_when_none_is_empty_and_unnamed() {
+        let workspaces = vec![
+            workspace(1, "eDP-1", None, Some(10)),
+            workspace(2, "eDP-1", Some("mail"), None),
+            workspace(1, "HDMI-A-1", None, None),
+        ];
+        assert!(find_empty_unnamed_workspace(&workspaces, "eDP-1").is_none());
+        assert_eq!(trailing_index(&workspaces, "eDP-1"), 3);
+        assert_eq!(trailing_index(&workspaces, "HDMI-A-1"), 1);
+        assert_eq!(trailing_index(&workspaces, "DP-2"), 1);
+    }
+
+    #[test]
+    fn test_garbage_skips_kept_used_and_focused() {
+        let named = vec![
+            named("mail", 0, false),
+            named("chat", 0, false),
+            named("web", 2, false),
+            named("notes", 0, true),
+        ];
+        let garbage: Vec<&str> = garbage(&named, &["mail".to_string()])
+            .iter()
+            .map(|ws| ws.name)
+            .collect();
+        assert_eq!(garbage, vec!["chat"]);
+    }
 }
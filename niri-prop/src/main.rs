@@ -1,84 +1,198 @@
 use clap::{arg, Command};
+use i3_prop::bar::{self, Window as BarWindow};
+use i3_prop::display::{self, Display, Render};
+use i3_prop::printer;
+use i3_prop::select::{self, Candidate, Selector};
 use niri_ipc::socket::Socket;
-use niri_ipc::{Event, Request, Response, Window};
+use niri_ipc::{Event, Request, Response, Window, Workspace};
+use reconnect::Backoff;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
+use std::io;
 
 #[derive(Serialize, Deserialize)]
 struct Props {
     window_id: u64,
+    pid: Option<i32>,
     title: Option<String>,
     app_id: Option<String>,
     workspace_id: Option<u64>,
     is_floating: bool,
 }
 
+/// Non-window events, printed as JSON lines tagged with `event`.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum EventProps {
+    Workspace {
+        change: String,
+        id: u64,
+        idx: Option<u8>,
+        name: Option<String>,
+        output: Option<String>,
+    },
+    Output {
+        change: String,
+        outputs: Vec<String>,
+    },
+}
+
+impl display::Event for EventProps {
+    fn kind(&self) -> &'static str {
+        match self {
+            EventProps::Workspace { .. } => "workspace",
+            EventProps::Output { .. } => "output",
+        }
+    }
+}
+
 fn cli() -> Command {
     Command::new("niri-prop")
         .about("Window properties query tool for niri")
+        .group(bar::group())
         .args(vec![
             arg!(-l --"listen" "Listen for focus changes"),
             arg!(-t --"title" "Print window title"),
-            arg!(-i --"app-id" "Prints window app_id"),
+            arg!(-e --"events" <EVENTS> "Comma separated event kinds to listen for")
+                .value_delimiter(',')
+                .value_parser(["window", "workspace", "output"])
+                .default_value("window"),
         ])
+        .args(bar::args())
+        .args(printer::args())
+        .args(select::args())
+        .arg(select::print_app_id_arg())
+        .arg(select::pid_arg())
 }
 
 fn main() {
     let matches = cli().get_matches();
 
-    let print_app_id = matches.get_flag("app-id");
-    let print_title = matches.get_flag("title");
+    let mut display = Display::from_matches(&matches, &["app-id", "title"], Windows);
+    display.header();
 
-    let print_props = !print_title && !print_app_id;
+    if let Some(selector) = Selector::from_matches(&matches) {
+        let (windows, workspaces) = get_windows_and_workspaces().unwrap_or_else(|e| {
+            eprintln!("Failed to get windows from niri: {e}");
+            std::process::exit(1);
+        });
+        let windows = windows
+            .iter()
+            .filter(|window| selector.matches(&candidate(window, &workspaces)))
+            .collect::<Vec<&Window>>();
 
-    if let Some(window) = get_focused_window() {
-        display_window(&window, print_props, print_app_id, print_title);
-    }
-
-    if matches.get_flag("listen") {
-        let mut socket = Socket::connect().expect("Failed to connect to niri socket");
-        let reply = socket
-            .send(Request::EventStream)
-            .expect("Failed to request event stream");
-
-        match reply {
-            Ok(Response::Handled) => {}
-            other => {
-                eprintln!("Unexpected reply to EventStream: {other:?}");
+        match (selector.is_single(), windows.first()) {
+            (true, Some(window)) => display.window(window),
+            (true, None) => {
+                eprintln!("No window matches given id");
                 std::process::exit(1);
             }
+            (false, _) => display.windows(&windows),
         }
+        return;
+    }
+
+    let listen = matches.get_flag("listen");
+    let events: Vec<&String> = matches
+        .get_many::<String>("events")
+        .map_or(vec![], |events| events.collect());
+    let listen_window = events.iter().any(|e| *e == "window");
+    let listen_workspace = events.iter().any(|e| *e == "workspace");
+    let listen_output = events.iter().any(|e| *e == "output");
 
+    if !listen || listen_window {
+        show_focused(&mut display);
+    }
+
+    if !listen {
+        return;
+    }
+
+    let mut socket = connect_event_stream().unwrap_or_else(|e| {
+        eprintln!("Failed to connect to niri event stream: {e}");
+        std::process::exit(1);
+    });
+    let mut backoff = Backoff::new();
+    // Kept across reconnects, so outputs changed meanwhile are still reported.
+    let mut outputs: Option<Vec<String>> = None;
+
+    loop {
         let mut read_event = socket.read_events();
+        let mut workspaces: Vec<Workspace> = vec![];
 
-        loop {
+        let error = loop {
             let event = match read_event() {
                 Ok(e) => e,
-                Err(e) => {
-                    eprintln!("Event stream error: {e}");
-                    break;
-                }
+                Err(e) => break e,
             };
+            backoff.reset();
 
             match event {
-                Event::WindowOpenedOrChanged { window } => {
-                    if window.is_focused {
-                        display_window(&window, print_props, print_app_id, print_title);
-                    }
+                Event::WindowOpenedOrChanged { window } if listen_window && window.is_focused => {
+                    display.window(&window);
+                }
+                Event::WindowFocusChanged { .. } | Event::WindowClosed { .. } if listen_window => {
+                    show_focused(&mut display);
                 }
-                Event::WindowFocusChanged { id } => {
-                    if id.is_some() {
-                        if let Some(window) = get_focused_window() {
-                            display_window(&window, print_props, print_app_id, print_title);
-                        }
+                Event::WorkspacesChanged {
+                    workspaces: changed,
+                } => {
+                    let current = workspace_outputs(&changed);
+                    if listen_output
+                        && !display.is_bar()
+                        && outputs.as_ref().is_some_and(|o| *o != current)
+                    {
+                        display.event(&EventProps::Output {
+                            change: "unspecified".to_string(),
+                            outputs: current.clone(),
+                        });
                     }
+                    outputs = Some(current);
+                    workspaces = changed;
+                }
+                Event::WorkspaceActivated { id, focused }
+                    if listen_workspace && !display.is_bar() =>
+                {
+                    let ws = workspaces.iter().find(|ws| ws.id == id);
+                    display.event(&EventProps::Workspace {
+                        change: if focused { "focus" } else { "activate" }.to_string(),
+                        id,
+                        idx: ws.map(|ws| ws.idx),
+                        name: ws.and_then(|ws| ws.name.clone()),
+                        output: ws.and_then(|ws| ws.output.clone()),
+                    });
                 }
                 _ => {}
             }
+        };
+        eprintln!("Event stream error: {error}");
+        display.disconnected(&error.to_string());
+
+        socket = backoff.retry(connect_event_stream);
+        if listen_window {
+            show_focused(&mut display);
         }
     }
 }
 
+fn connect_event_stream() -> io::Result<Socket> {
+    let mut socket = Socket::connect()?;
+    match socket.send(Request::EventStream)? {
+        Ok(Response::Handled) => Ok(socket),
+        other => Err(io::Error::other(format!(
+            "Unexpected reply to EventStream: {other:?}"
+        ))),
+    }
+}
+
+/// Prints the focused window, bars are cleared when nothing is focused.
+fn show_focused(display: &mut Display<Windows>) {
+    match get_focused_window() {
+        Some(window) => display.window(&window),
+        None => display.no_window(),
+    }
+}
+
 fn get_focused_window() -> Option<Window> {
     let mut socket = Socket::connect().ok()?;
     let reply = socket.send(Request::FocusedWindow).ok()?;
@@ -88,33 +202,91 @@ fn get_focused_window() -> Option<Window> {
     }
 }
 
-fn display_window(window: &Window, print_props: bool, print_app_id: bool, print_title: bool) {
-    if print_props {
-        display_props(window);
+fn get_windows_and_workspaces() -> Result<(Vec<Window>, Vec<Workspace>), String> {
+    let mut socket = Socket::connect().map_err(|e| e.to_string())?;
+    let windows = match socket.send(Request::Windows).map_err(|e| e.to_string())? {
+        Ok(Response::Windows(windows)) => windows,
+        other => return Err(format!("unexpected reply to Windows: {other:?}")),
+    };
+    let workspaces = match socket
+        .send(Request::Workspaces)
+        .map_err(|e| e.to_string())?
+    {
+        Ok(Response::Workspaces(workspaces)) => workspaces,
+        other => return Err(format!("unexpected reply to Workspaces: {other:?}")),
+    };
+    Ok((windows, workspaces))
+}
+
+fn candidate<'a>(window: &'a Window, workspaces: &[Workspace]) -> Candidate<'a> {
+    let workspace = workspaces
+        .iter()
+        .find(|ws| Some(ws.id) == window.workspace_id);
+    Candidate {
+        id: window.id.to_string(),
+        pid: window.pid,
+        apps: window.app_id.as_deref().into_iter().collect(),
+        workspaces: workspace.map_or(vec![], |ws| {
+            [ws.name.clone(), Some(ws.idx.to_string())]
+                .into_iter()
+                .flatten()
+                .collect()
+        }),
+    }
+}
+
+/// niri keeps at least one workspace on every connected output, so the set of
+/// workspace outputs changes exactly when an output is added or removed.
+fn workspace_outputs(workspaces: &[Workspace]) -> Vec<String> {
+    let mut outputs: Vec<String> = workspaces
+        .iter()
+        .filter_map(|ws| ws.output.clone())
+        .collect();
+    outputs.sort();
+    outputs.dedup();
+    outputs
+}
+
+/// niri windows as printed by the tool.
+struct Windows;
+
+impl Render for Windows {
+    type Window = Window;
+
+    fn bar_window<'a>(&self, window: &'a Window) -> BarWindow<'a> {
+        BarWindow {
+            app: window.app_id.as_deref(),
+            title: window.title.as_deref(),
+        }
     }
-    if print_app_id {
-        display_app_id(window);
+
+    fn props(&self, window: &Window) -> Value {
+        json!(props(window))
     }
-    if print_title {
-        display_title(window);
+
+    fn field(&self, window: &Window, flag: &str) -> String {
+        match flag {
+            "app-id" => render_app_id(window),
+            _ => render_title(window),
+        }
     }
 }
 
-fn display_props(window: &Window) {
-    let props = Props {
-        window_id: window.id.clone(),
+fn props(window: &Window) -> Props {
+    Props {
+        window_id: window.id,
+        pid: window.pid,
         title: window.title.clone(),
         app_id: window.app_id.clone(),
-        workspace_id: window.workspace_id.clone(),
+        workspace_id: window.workspace_id,
         is_floating: window.is_floating,
-    };
-    println!("{}", json!(props));
+    }
 }
 
-fn display_title(window: &Window) {
-    println!("{}", window.title.clone().map_or_else(String::new, |t| t));
+fn render_title(window: &Window) -> String {
+    window.title.clone().unwrap_or_default()
 }
 
-fn display_app_id(window: &Window) {
-    println!("{}", window.app_id.clone().map_or_else(String::new, |t| t));
+fn render_app_id(window: &Window) -> String {
+    window.app_id.clone().unwrap_or_default()
 }
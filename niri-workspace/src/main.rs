@@ -0,0 +1,184 @@
+use clap::Command;
+use niri_ipc::socket::Socket;
+use niri_ipc::{Action, Event, Request, Response, WorkspaceReferenceArg};
+use std::io;
+use sway_workspace::{Compositor, FocusEvents, Options, Target, Workspace};
+
+fn cli() -> Command {
+    Command::new("niri-workspace")
+        .about("Manage workspaces in niri")
+        .args(sway_workspace::args())
+}
+
+/// niri workspaces are dynamic: every output has its workspaces ordered by
+/// index, with an empty one at the end. Unnamed workspaces count as numbered,
+/// and `--new` goes to the empty workspace of the focused output, ranges do
+/// not apply.
+struct Niri {
+    workspaces: Vec<niri_ipc::Workspace>,
+    dry_run: bool,
+}
+
+impl Niri {
+    fn request(request: Request) -> io::Result<Response> {
+        let mut socket = Socket::connect()?;
+        socket.send(request)?.map_err(io::Error::other)
+    }
+
+    fn action(&self, action: Action) -> io::Result<()> {
+        if self.dry_run {
+            println!("{action:?}");
+            return Ok(());
+        }
+        Niri::request(Request::Action(action))?;
+        Ok(())
+    }
+
+    fn reference(&self, target: &Target) -> io::Result<WorkspaceReferenceArg> {
+        match target {
+            Target::Workspace(ws) => Ok(WorkspaceReferenceArg::Id(ws.id as u64)),
+            Target::New(num) => Err(io::Error::other(format!(
+                "Can not create workspace {num}, niri numbers workspaces by position"
+            ))),
+        }
+    }
+}
+
+/// The empty workspace niri keeps at the end of the focused output, workspaces
+/// are ordered by output and index.
+fn trailing_workspace(workspaces: Vec<Workspace>) -> Option<Workspace> {
+    let output = workspaces.iter().find(|ws| ws.focused)?.output.clone();
+    workspaces
+        .into_iter()
+        .rev()
+        .find(|ws| ws.output == output)
+        .filter(|ws| ws.empty && ws.num.is_some())
+}
+
+impl Compositor for Niri {
+    type Error = io::Error;
+
+    fn workspaces(&mut self) -> io::Result<Vec<Workspace>> {
+        self.workspaces = match Niri::request(Request::Workspaces)? {
+            Response::Workspaces(workspaces) => workspaces,
+            other => return Err(io::Error::other(format!("Unexpected reply: {other:?}"))),
+        };
+        self.workspaces
+            .sort_by(|a, b| (&a.output, a.idx).cmp(&(&b.output, b.idx)));
+
+        Ok(self
+            .workspaces
+            .iter()
+            .map(|ws| Workspace {
+                id: ws.id as i64,
+                name: ws.name.clone().unwrap_or_else(|| ws.idx.to_string()),
+                num: ws.name.is_none().then_some(ws.idx as i32),
+                output: ws.output.clone().unwrap_or_default(),
+                focused: ws.is_focused,
+                visible: ws.is_active,
+                empty: ws.active_window_id.is_none(),
+            })
+            .collect())
+    }
+
+    fn focus(&mut self, target: &Target) -> io::Result<()> {
+        let reference = self.reference(target)?;
+        self.action(Action::FocusWorkspace { reference })
+    }
+
+    fn move_window(&mut self, target: &Target) -> io::Result<()> {
+        let reference = self.reference(target)?;
+        self.action(Action::MoveWindowToWorkspace {
+            window_id: None,
+            reference,
+            focus: false,
+        })
+    }
+
+    fn rename(&mut self, ws: &Workspace, _name: &str) -> io::Result<()> {
+        Err(io::Error::other(format!(
+            "Can not renumber workspace {}, niri numbers workspaces by position",
+            ws.name
+        )))
+    }
+
+    fn outputs(&mut self) -> io::Result<Vec<String>> {
+        let outputs = match Niri::request(Request::Outputs)? {
+            Response::Outputs(outputs) => outputs,
+            other => return Err(io::Error::other(format!("Unexpected reply: {other:?}"))),
+        };
+        let mut outputs: Vec<(String, (i32, i32))> = outputs
+            .into_iter()
+            .filter_map(|(name, output)| Some((name, output.logical.map(|l| (l.x, l.y))?)))
+            .collect();
+        outputs.sort_by_key(|(_, position)| *position);
+        Ok(outputs.into_iter().map(|(name, _)| name).collect())
+    }
+
+    fn move_workspace(&mut self, ws: &Workspace, output: &str) -> io::Result<()> {
+        self.action(Action::MoveWorkspaceToMonitor {
+            output: output.to_string(),
+            reference: Some(WorkspaceReferenceArg::Id(ws.id as u64)),
+        })
+    }
+
+    fn new_workspace(&mut self, workspaces: Vec<Workspace>, _range: (i32, i32)) -> Option<Target> {
+        trailing_workspace(workspaces).map(Target::Workspace)
+    }
+
+    fn focus_events(&mut self) -> io::Result<FocusEvents<io::Error>> {
+        let mut socket = Socket::connect()?;
+        socket
+            .send(Request::EventStream)?
+            .map_err(io::Error::other)?;
+        let mut read_event = socket.read_events();
+        Ok(Box::new(
+            std::iter::from_fn(move || Some(read_event())).filter_map(|event| match event {
+                Ok(Event::WorkspaceActivated { focused: true, .. }) => Some(Ok(())),
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            }),
+        ))
+    }
+}
+
+fn main() {
+    let matches = cli().get_matches();
+
+    let options = Options::from_matches(&matches);
+    let mut niri = Niri {
+        workspaces: vec![],
+        dry_run: options.dry_run,
+    };
+    sway_workspace::report(sway_workspace::run(&mut niri, &options), &options);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ws(idx: i32, output: &str, focused: bool, empty: bool) -> Workspace {
+        Workspace {
+            id: idx as i64,
+            name: idx.to_string(),
+            num: Some(idx),
+            output: output.to_string(),
+            focused,
+            visible: focused,
+            empty,
+        }
+    }
+
+    #[test]
+    fn test_new_is_trailing_workspace_of_focused_output() {
+        let workspaces = vec![
+            ws(1, "DP-1", true, false),
+            ws(2, "DP-1", false, false),
+            ws(3, "DP-1", false, true),
+            ws(1, "HDMI-A-1", false, false),
+            ws(5, "HDMI-A-1", false, true),
+        ];
+        let trailing = trailing_workspace(workspaces).unwrap();
+        assert_eq!((trailing.num, trailing.output.as_str()), (Some(3), "DP-1"));
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt::Display;
+use std::thread;
+use std::time::Duration;
+
+const MIN_DELAY: Duration = Duration::from_millis(100);
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Delay between reconnection attempts, doubled after every failure so a
+/// compositor that is gone for good is not hammered. It only goes back down
+/// once a connection delivered an event, a stream that breaks right away counts
+/// as a failure too.
+pub struct Backoff {
+    delay: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Backoff {
+    pub fn new() -> Self {
+        Self { delay: MIN_DELAY }
+    }
+
+    /// Call when the connection delivered an event, it works again.
+    pub fn reset(&mut self) {
+        self.delay = MIN_DELAY;
+    }
+
+    /// Waits, then calls `connect` until it succeeds, sleeping longer after
+    /// every failure.
+    pub fn retry<T, E: Display>(&mut self, mut connect: impl FnMut() -> Result<T, E>) -> T {
+        loop {
+            thread::sleep(self.next_delay());
+            match connect() {
+                Ok(connection) => return connection,
+                Err(e) => eprintln!("Reconnecting in {}ms: {e}", self.delay.as_millis()),
+            }
+        }
+    }
+
+    fn next_delay(&mut self) -> Duration {
+        let delay = self.delay;
+        self.delay = (self.delay * 2).min(MAX_DELAY);
+        delay
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_delay_doubles_up_to_max() {
+        let mut backoff = Backoff::new();
+        assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+        assert_eq!(backoff.next_delay(), Duration::from_millis(200));
+        assert_eq!(backoff.next_delay(), Duration::from_millis(400));
+        for _ in 0..20 {
+            backoff.next_delay();
+        }
+        assert_eq!(backoff.next_delay(), MAX_DELAY);
+    }
+
+    #[test]
+    fn test_delay_resets_only_after_event() {
+        let mut backoff = Backoff::new();
+        let mut attempts = 0;
+        let value = backoff.retry(|| {
+            attempts += 1;
+            match attempts {
+                1 => Err("down"),
+                _ => Ok(attempts),
+            }
+        });
+        assert_eq!(value, 2);
+        // Connected, but nothing came through yet.
+        assert_eq!(backoff.next_delay(), Duration::from_millis(400));
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), MIN_DELAY);
+    }
+}
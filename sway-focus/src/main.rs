@@ -2,7 +2,7 @@ use clap::{arg, value_parser, Command};
 use i3_focus::{
     nvim, tmux,
     wezterm::{self, WezTermId},
-    zellij, Direction,
+    zellij, Direction, FloatingPolicy,
 };
 use swayipc::Connection;
 use swayipc_types::Node;
@@ -13,6 +13,9 @@ fn cli() -> Command {
         .args(vec![
             arg!(<DIRECTION> "Focus direction").value_parser(value_parser!(Direction)),
             arg!(--"skip-nvim" "Skip nvim check"),
+            arg!(--"floating" <POLICY> "How to leave a floating window")
+                .value_parser(value_parser!(FloatingPolicy))
+                .default_value("direction"),
         ])
 }
 
@@ -25,7 +28,16 @@ fn main() {
         .get_one::<Direction>("DIRECTION")
         .expect("Direction has to be provided");
 
-    match get_focused_name(&mut sway) {
+    let tree = sway.get_tree().ok();
+    let policy = if tree.as_ref().is_some_and(is_focused_floating) {
+        matches
+            .get_one::<FloatingPolicy>("floating")
+            .expect("Floating policy has a default")
+    } else {
+        &FloatingPolicy::Direction
+    };
+
+    match tree.as_ref().and_then(get_focused_name) {
         Some(name) => {
             let skip_vim = matches.get_flag("skip-nvim");
             let nvim_id = get_nvim_id(&name);
@@ -55,15 +67,16 @@ fn main() {
                 }
             }
 
-            handle_sway(&mut sway, direction);
+            handle_sway(&mut sway, direction, policy);
         }
-        None => handle_sway(&mut sway, direction),
+        None => handle_sway(&mut sway, direction, policy),
     }
 }
 
-fn handle_sway(sway: &mut Connection, direction: &Direction) {
-    sway.run_command(format!("focus {}", direction))
-        .unwrap_or_default();
+fn handle_sway(sway: &mut Connection, direction: &Direction, policy: &FloatingPolicy) {
+    if let Some(command) = policy.command(direction) {
+        sway.run_command(command).unwrap_or_default();
+    }
 }
 
 fn handle_nvim(id: usize, direction: &Direction) {
@@ -82,17 +95,12 @@ fn handle_zellij(id: &str, direction: &Direction) -> bool {
     zellij::focus(id, direction)
 }
 
-fn get_focused_name(sway: &mut Connection) -> Option<String> {
-    match sway.get_tree() {
-        Ok(tree) => {
-            let focused_list = get_focused(&tree);
+fn get_focused_name(tree: &Node) -> Option<String> {
+    let focused_list = get_focused(tree);
 
-            match focused_list.first() {
-                Some(focused) => focused.name.clone(),
-                None => None,
-            }
-        }
-        Err(_) => None,
+    match focused_list.first() {
+        Some(focused) => focused.name.clone(),
+        None => None,
     }
 }
 
@@ -147,9 +155,19 @@ fn collect_focused<'a>(node: &'a Node, mut r: Vec<&'a Node>) -> Vec<&'a Node> {
     for n in &node.nodes {
         r = collect_focused(n, r)
     }
+    for n in &node.floating_nodes {
+        r = collect_focused(n, r)
+    }
     r
 }
 
+fn is_focused_floating(node: &Node) -> bool {
+    node.floating_nodes
+        .iter()
+        .any(|n| !get_focused(n).is_empty())
+        || node.nodes.iter().any(is_focused_floating)
+}
+
 fn get_focused(node: &Node) -> Vec<&Node> {
     let v: Vec<&Node> = vec![];
     collect_focused(node, v)
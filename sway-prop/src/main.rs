@@ -1,137 +1,240 @@
 use clap::{arg, Command};
+use i3_prop::bar::{self, Window};
+use i3_prop::display::{self, Display, Render};
+use i3_prop::printer;
+use i3_prop::select::{self, Candidate, Selector};
+use reconnect::Backoff;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
 use swayipc::{Connection, EventType, Fallible};
-use swayipc_types::{Event, Node, WindowProperties};
+use swayipc_types::{Event, Node, NodeType, WindowChange, WindowProperties, WorkspaceChange};
 
 #[derive(Serialize, Deserialize)]
 struct Props {
+    id: i64,
+    pid: Option<i32>,
     title: Option<String>,
     app_id: Option<String>,
     class: Option<String>,
     instance: Option<String>,
 }
 
+type EventProps = display::EventProps<WorkspaceChange>;
+
 fn cli() -> Command {
     Command::new("i3-prop")
         .about("Window properties quiry tool for i3 / sway")
+        .group(bar::group())
         .args(vec![
             arg!(-l --"listen" "Listen for focus changes"),
             arg!(-t --"title" "Print window title"),
-            arg!(-i --"app-id" "Prints window class"),
             arg!(--"x-class" "Prints window class"),
             arg!(-x --"x-instance-class" "Prints window instance and class"),
+            arg!(-e --"events" <EVENTS> "Comma separated event kinds to listen for")
+                .value_delimiter(',')
+                .value_parser(["window", "workspace", "mode", "output"])
+                .default_value("window"),
         ])
+        .args(bar::args())
+        .args(printer::args())
+        .args(select::args())
+        .arg(select::print_app_id_arg())
+        .arg(select::pid_arg())
 }
 
 fn main() -> Fallible<()> {
     let matches = cli().get_matches();
 
-    let subs = [EventType::Window];
+    let listen = matches.get_flag("listen");
+    let events: Vec<&String> = matches
+        .get_many::<String>("events")
+        .map_or(vec![], |events| events.collect());
+    let listen_window = events.iter().any(|e| *e == "window");
+    let listen_workspace = events.iter().any(|e| *e == "workspace");
+    let listen_output = events.iter().any(|e| *e == "output");
 
-    let print_app_id = matches.get_flag("app-id");
-    let print_title = matches.get_flag("title");
-    let print_class = matches.get_flag("x-class");
-    let print_icls = matches.get_flag("x-instance-class");
+    let flags = ["app-id", "title", "x-class", "x-instance-class"];
+    let mut display = Display::from_matches(&matches, &flags, Nodes);
+    display.header();
 
-    let print_props = !print_title && !print_class && !print_icls && !print_app_id;
+    let subs = subscriptions(&events, display.is_bar());
 
     let mut sway = Connection::new()?;
 
-    let _ = get_focused_node(&mut sway).map_or((), |node| {
-        display_node(
-            &node,
-            print_props,
-            print_app_id,
-            print_title,
-            print_class,
-            print_icls,
-        );
-    });
-
-    if matches.get_flag("listen") {
-        for event in (sway.subscribe(subs)?).flatten() {
-            if let Event::Window(ev) = event {
-                display_node(
-                    &ev.container,
-                    print_props,
-                    print_app_id,
-                    print_title,
-                    print_class,
-                    print_icls,
-                );
+    if let Some(selector) = Selector::from_matches(&matches) {
+        let tree = sway.get_tree()?;
+        let windows = get_windows(&tree)
+            .into_iter()
+            .filter(|(node, workspace)| selector.matches(&candidate(node, *workspace)))
+            .map(|(node, _)| node)
+            .collect::<Vec<&Node>>();
+
+        match (selector.is_single(), windows.first()) {
+            (true, Some(node)) => display.window(node),
+            (true, None) => {
+                eprintln!("No window matches given id");
+                std::process::exit(1);
             }
+            (false, _) => display.windows(&windows),
         }
+        return Ok(());
+    }
+
+    if !listen || listen_window {
+        show_focused(&mut display);
+    }
+
+    if !listen {
+        return Ok(());
     }
 
-    Ok(())
+    let mut outputs = get_active_outputs();
+    let mut events = sway.subscribe(&subs)?;
+    let mut backoff = Backoff::new();
+    loop {
+        let error = loop {
+            let event = match events.next() {
+                Some(Ok(event)) => event,
+                Some(Err(e)) => break e.to_string(),
+                None => break "connection closed".to_string(),
+            };
+            backoff.reset();
+            match event {
+                // Closing the last window or switching to an empty workspace
+                // focuses no window, bars have to be cleared.
+                Event::Window(ev) if display.is_bar() && ev.change == WindowChange::Close => {
+                    show_focused(&mut display)
+                }
+                Event::Workspace(_) if display.is_bar() => show_focused(&mut display),
+                // Title, mark or urgency changes of other windows are not ours.
+                Event::Window(ev) if ev.container.focused => display.window(&ev.container),
+                _ if display.is_bar() => (),
+                Event::Workspace(ev) => {
+                    if listen_workspace {
+                        display.event(&EventProps::Workspace {
+                            change: ev.change,
+                            name: ev.current.as_ref().and_then(|n| n.name.clone()),
+                            num: ev.current.as_ref().and_then(|n| n.num),
+                            output: ev.current.as_ref().and_then(|n| n.output.clone()),
+                            old: ev.old.as_ref().and_then(|n| n.name.clone()),
+                        });
+                    }
+                    if listen_output {
+                        let current = get_active_outputs();
+                        if current != outputs {
+                            outputs = current;
+                            display.event(&EventProps::Output {
+                                change: "unspecified".to_string(),
+                                outputs: outputs.clone(),
+                            });
+                        }
+                    }
+                }
+                Event::Mode(ev) => display.event(&EventProps::Mode { change: ev.change }),
+                _ => (),
+            }
+        };
+        eprintln!("Event stream error: {error}");
+        display.disconnected(&error);
+
+        events = backoff.retry(|| Connection::new()?.subscribe(&subs));
+        if listen_window {
+            show_focused(&mut display);
+        }
+    }
 }
 
-fn display_node(
-    node: &Node,
-    print_props: bool,
-    print_app_id: bool,
-    print_title: bool,
-    print_class: bool,
-    print_icls: bool,
-) {
-    if print_props {
-        display_props(&node);
+/// swayipc can not subscribe to output events, but sway reshuffles workspaces
+/// whenever an output comes or goes, so workspace events are used to notice it.
+/// Bars follow workspace changes too, an empty workspace has no window event
+/// to clear them.
+fn subscriptions(events: &[&String], bar: bool) -> Vec<EventType> {
+    let mut subs: Vec<EventType> = vec![];
+    if bar {
+        subs.push(EventType::Workspace);
     }
-    if print_app_id {
-        display_app_id(&node);
+    for event in events {
+        let sub = match event.as_str() {
+            "workspace" | "output" => EventType::Workspace,
+            "mode" => EventType::Mode,
+            _ => EventType::Window,
+        };
+        if !subs.contains(&sub) {
+            subs.push(sub);
+        }
     }
-    if print_title {
-        display_title(&node);
+    subs
+}
+
+/// sway tree nodes as printed by the tool.
+struct Nodes;
+
+impl Render for Nodes {
+    type Window = Node;
+
+    fn bar_window<'a>(&self, node: &'a Node) -> Window<'a> {
+        let class = node
+            .window_properties
+            .as_ref()
+            .and_then(|p| p.class.as_deref());
+        Window {
+            app: node.app_id.as_deref().or(class),
+            title: node.name.as_deref(),
+        }
     }
-    if print_class {
-        display_class(&node.window_properties);
+
+    fn props(&self, node: &Node) -> Value {
+        json!(props(node))
     }
-    if print_icls {
-        display_icls(&node.window_properties);
+
+    fn field(&self, node: &Node, flag: &str) -> String {
+        match flag {
+            "app-id" => render_app_id(node),
+            "title" => render_title(node),
+            "x-class" => render_class(&node.window_properties),
+            _ => render_icls(&node.window_properties),
+        }
     }
 }
 
-fn display_props(node: &Node) {
-    let props = Props {
+fn props(node: &Node) -> Props {
+    Props {
+        id: node.id,
+        pid: node.pid,
         title: node.name.clone(),
         app_id: node.app_id.clone(),
         class: node.window_properties.clone().and_then(|p| p.class),
         instance: node.window_properties.clone().and_then(|p| p.instance),
-    };
-    println!("{}", json!(props));
+    }
 }
 
-fn display_title(node: &Node) {
-    println!("{}", node.name.clone().map_or_else(String::new, |t| t));
+fn render_title(node: &Node) -> String {
+    node.name.clone().unwrap_or_default()
 }
 
-fn display_app_id(node: &Node) {
-    println!("{}", node.app_id.clone().map_or_else(String::new, |t| t));
+fn render_app_id(node: &Node) -> String {
+    node.app_id.clone().unwrap_or_default()
 }
 
-fn display_class(window_properties: &Option<WindowProperties>) {
-    println!(
-        "{}",
-        window_properties
-            .clone()
-            .and_then(|p| p.class)
-            .map_or_else(String::new, |t| t)
-    );
+fn render_class(window_properties: &Option<WindowProperties>) -> String {
+    window_properties
+        .clone()
+        .and_then(|p| p.class)
+        .unwrap_or_default()
 }
 
-fn display_icls(window_properties: &Option<WindowProperties>) {
-    println!(
+fn render_icls(window_properties: &Option<WindowProperties>) -> String {
+    format!(
         "{} {}",
         window_properties
             .clone()
             .and_then(|p| p.instance)
-            .map_or_else(String::new, |t| t),
+            .unwrap_or_default(),
         window_properties
             .clone()
             .and_then(|p| p.class)
-            .map_or_else(String::new, |t| t)
-    );
+            .unwrap_or_default()
+    )
 }
 
 fn collect_focused<'a>(node: &'a Node, mut r: Vec<&'a Node>) -> Vec<&'a Node> {
@@ -152,9 +255,80 @@ fn get_focused(node: &Node) -> Vec<&Node> {
     collect_focused(node, v)
 }
 
+/// Prints the focused window, bars are cleared when a workspace is focused.
+fn show_focused(display: &mut Display<Nodes>) {
+    let node = Connection::new()
+        .ok()
+        .and_then(|mut sway| get_focused_node(&mut sway));
+    match node {
+        Some(node) if node.pid.is_some() => display.window(&node),
+        _ => display.no_window(),
+    }
+}
+
 fn get_focused_node(sway: &mut Connection) -> Option<Node> {
     sway.get_tree().map_or(None, |tree| {
         let focused_list = get_focused(&tree);
         focused_list.first().map(|&node| node.clone())
     })
 }
+
+fn get_active_outputs() -> Vec<String> {
+    Connection::new()
+        .and_then(|mut sway| sway.get_outputs())
+        .map_or(vec![], |outputs| {
+            outputs
+                .into_iter()
+                .filter(|output| output.active)
+                .map(|output| output.name)
+                .collect()
+        })
+}
+
+fn candidate<'a>(node: &'a Node, workspace: Option<&'a Node>) -> Candidate<'a> {
+    let window_properties = node.window_properties.as_ref();
+    Candidate {
+        id: node.id.to_string(),
+        pid: node.pid,
+        apps: [
+            node.app_id.as_deref(),
+            window_properties.and_then(|p| p.class.as_deref()),
+            window_properties.and_then(|p| p.instance.as_deref()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+        workspaces: workspace.map_or(vec![], |ws| {
+            [ws.name.clone(), ws.num.map(|num| num.to_string())]
+                .into_iter()
+                .flatten()
+                .collect()
+        }),
+    }
+}
+
+/// Collect all windows together with the workspace they are on.
+fn collect_windows<'a>(
+    node: &'a Node,
+    workspace: Option<&'a Node>,
+    mut r: Vec<(&'a Node, Option<&'a Node>)>,
+) -> Vec<(&'a Node, Option<&'a Node>)> {
+    let workspace = match node.node_type {
+        NodeType::Workspace => Some(node),
+        _ => workspace,
+    };
+    if node.pid.is_some() {
+        r.push((node, workspace));
+    }
+    for n in &node.nodes {
+        r = collect_windows(n, workspace, r);
+    }
+    for n in &node.floating_nodes {
+        r = collect_windows(n, workspace, r);
+    }
+    r
+}
+
+fn get_windows(node: &Node) -> Vec<(&Node, Option<&Node>)> {
+    collect_windows(node, None, vec![])
+}
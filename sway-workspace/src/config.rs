@@ -0,0 +1,117 @@
+use crate::scratch::Scratch;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Workspace tools config, read from `$XDG_CONFIG_HOME/i-use-rust-btw/workspace.toml`:
+///
+/// ```toml
+/// # labels used by rename, apps without an icon are labelled by their name
+/// default-icon = "?"
+///
+/// # numbers owned by every output, used by --new and --renumber
+/// [ranges]
+/// "DP-1" = [1, 9]
+/// "HDMI-A-1" = [11, 19]
+///
+/// [icons]
+/// firefox = "web"
+/// "org.wezfurlong.wezterm" = "term"
+///
+/// # used by scratch, see `Scratch`
+/// [scratch.notes]
+/// app-id = "^notes$"
+/// command = "wezterm start --class notes nvim ~/notes"
+/// ```
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    #[serde(default)]
+    pub ranges: HashMap<String, (i32, i32)>,
+    #[serde(default)]
+    pub default_icon: String,
+    #[serde(default)]
+    pub icons: HashMap<String, String>,
+    #[serde(default)]
+    pub scratch: HashMap<String, Scratch>,
+}
+
+impl Config {
+    /// Load config from `path` or the default location. Missing file means
+    /// defaults, broken one is reported and ends the process.
+    pub fn load(path: Option<&str>) -> Config {
+        let path = match path {
+            Some(path) => path.to_string(),
+            None => default_config_path(),
+        };
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return Config::default(),
+        };
+        toml::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("Can not parse config {path}: {e}");
+            std::process::exit(1);
+        })
+    }
+
+    /// Icon for app id / class matched case insensitive, falls back to the
+    /// default icon and then to the app itself.
+    pub fn icon<'a>(&'a self, app: &'a str) -> &'a str {
+        self.icons
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(app))
+            .map(|(_, icon)| icon.as_str())
+            .or((!self.default_icon.is_empty()).then_some(self.default_icon.as_str()))
+            .unwrap_or(app)
+    }
+}
+
+/// Parses `--range` value, e.g. `DP-1=1-9`.
+pub fn parse_range(value: &str) -> Result<(String, (i32, i32)), String> {
+    let error = || format!("expected OUTPUT=FROM-TO, got {value}");
+    let (output, range) = value.rsplit_once('=').ok_or_else(error)?;
+    let (from, to) = range.split_once('-').ok_or_else(error)?;
+    let from = from.trim().parse().map_err(|_| error())?;
+    let to = to.trim().parse().map_err(|_| error())?;
+    Ok((output.to_string(), (from, to)))
+}
+
+fn default_config_path() -> String {
+    let dir = std::env::var("XDG_CONFIG_HOME").unwrap_or_else(|_| {
+        let home = std::env::var("HOME").unwrap_or("/tmp".into());
+        format!("{home}/.config")
+    });
+    format!("{dir}/i-use-rust-btw/workspace.toml")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("DP-1=1-9"), Ok(("DP-1".to_string(), (1, 9))));
+        assert!(parse_range("DP-1").is_err());
+        assert!(parse_range("DP-1=a-9").is_err());
+    }
+
+    #[test]
+    fn test_ranges_from_toml() {
+        let config: Config = toml::from_str("[ranges]\n\"DP-1\" = [1, 9]\n").unwrap();
+        assert_eq!(config.ranges.get("DP-1"), Some(&(1, 9)));
+    }
+
+    #[test]
+    fn test_invalid_scratch_regex_fails_to_load() {
+        assert!(toml::from_str::<Config>("[scratch.term]\napp-id = \"(\"\n").is_err());
+    }
+
+    #[test]
+    fn test_scratch_from_toml() {
+        let config: Config =
+            toml::from_str("[scratch.term]\napp-id = \"term\"\nsize = [80, 60]\n").unwrap();
+        let term = &config.scratch["term"];
+        assert!(term.app_id.as_ref().unwrap().is_match("scratch-term"));
+        assert_eq!(term.size, Some((80, 60)));
+        assert!(term.outputs.is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+use crate::Workspace;
+use std::io::Write;
+
+/// How many workspaces are remembered.
+const MAX_ENTRIES: usize = 32;
+
+/// Recently focused workspaces, most recent first. Kept in
+/// `$XDG_RUNTIME_DIR/workspace_history` as `<id>\t<name>` lines, so it is
+/// shared by all outputs and survives restarts of the watcher.
+#[derive(Default)]
+pub struct History {
+    entries: Vec<(i64, String)>,
+}
+
+impl History {
+    /// Missing or broken file means empty history.
+    pub fn load() -> History {
+        let content = std::fs::read_to_string(history_file_path()).unwrap_or_default();
+        let entries = content
+            .lines()
+            .filter_map(|line| {
+                let (id, name) = line.split_once('\t')?;
+                Some((id.parse().ok()?, name.to_string()))
+            })
+            .collect();
+        History { entries }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let mut file = std::fs::File::create(history_file_path())?;
+        for (id, name) in &self.entries {
+            writeln!(file, "{id}\t{name}")?;
+        }
+        Ok(())
+    }
+
+    /// Puts workspace on top, returns `false` if it was there already.
+    pub fn record(&mut self, ws: &Workspace) -> bool {
+        if self.entries.first().is_some_and(|(id, _)| *id == ws.id) {
+            return false;
+        }
+        self.entries.retain(|(id, _)| *id != ws.id);
+        self.entries.insert(0, (ws.id, ws.name.clone()));
+        self.entries.truncate(MAX_ENTRIES);
+        true
+    }
+
+    /// Most recent workspace that still exists and is not focused. Workspaces
+    /// are matched by id, then by name as sway and i3 recreate them with new
+    /// ids.
+    pub fn previous(&self, mut workspaces: Vec<Workspace>) -> Option<Workspace> {
+        let idx = self.entries.iter().find_map(|(id, name)| {
+            workspaces
+                .iter()
+                .position(|ws| ws.id == *id)
+                .or_else(|| workspaces.iter().position(|ws| ws.name == *name))
+                .filter(|&idx| !workspaces[idx].focused)
+        })?;
+        Some(workspaces.swap_remove(idx))
+    }
+}
+
+fn history_file_path() -> String {
+    let dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or("/tmp".into());
+    format!("{dir}/workspace_history")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ws(id: i64, name: &str, focused: bool) -> Workspace {
+        Workspace {
+            id,
+            name: name.to_string(),
+            num: name.parse().ok(),
+            output: "DP-1".to_string(),
+            focused,
+            visible: focused,
+            empty: false,
+        }
+    }
+
+    #[test]
+    fn test_record_moves_workspace_to_top() {
+        let mut history = History::default();
+        assert!(history.record(&ws(1, "1", true)));
+        assert!(history.record(&ws(2, "2", true)));
+        assert!(!history.record(&ws(2, "2", true)));
+        assert!(history.record(&ws(1, "1", true)));
+        assert_eq!(
+            history.entries,
+            vec![(1, "1".to_string()), (2, "2".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_previous_skips_focused_and_gone() {
+        let mut history = History::default();
+        for (id, name) in [(3, "3"), (2, "2"), (1, "1")] {
+            history.record(&ws(id, name, false));
+        }
+        // 1 is focused, 2 is gone and 3 got recreated with a new id
+        let workspaces = vec![ws(1, "1", true), ws(30, "3", false)];
+        assert_eq!(history.previous(workspaces).map(|ws| ws.id), Some(30));
+    }
+}
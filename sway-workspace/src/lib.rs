@@ -0,0 +1,764 @@
+use clap::{arg, value_parser, Arg, ArgAction, ArgMatches};
+use config::Config;
+use history::History;
+use regex::Regex;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+pub mod config;
+pub mod history;
+pub mod scratch;
+
+pub enum Direction {
+    Next,
+    Prev,
+}
+
+/// Workspace as far as cycling is concerned.
+pub struct Workspace {
+    /// Id used by the compositor to address the workspace.
+    pub id: i64,
+    pub name: String,
+    /// `None` for named workspaces.
+    pub num: Option<i32>,
+    pub output: String,
+    pub focused: bool,
+    /// Shown on its output, focused or not.
+    pub visible: bool,
+    pub empty: bool,
+}
+
+/// Workspace to go to, an existing one or a new numbered one.
+pub enum Target {
+    Workspace(Workspace),
+    New(i32),
+}
+
+impl Target {
+    /// Name to use in `workspace <name>` like commands.
+    pub fn name(&self) -> String {
+        match self {
+            Target::Workspace(ws) => ws.name.clone(),
+            Target::New(num) => num.to_string(),
+        }
+    }
+}
+
+/// Workspace chosen by `run`, printed by `--json`.
+#[derive(Debug, Serialize)]
+pub struct Outcome {
+    pub name: String,
+    pub num: Option<i32>,
+    /// Output the workspace is on afterwards.
+    pub output: String,
+    /// Workspace did not exist before.
+    pub created: bool,
+    /// Focused window was moved or sent there.
+    pub window_moved: bool,
+    pub focused: bool,
+}
+
+impl Outcome {
+    fn moved(ws: &Workspace, output: String) -> Self {
+        Outcome {
+            name: ws.name.clone(),
+            num: ws.num,
+            output,
+            created: false,
+            window_moved: false,
+            focused: true,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error<E> {
+    Compositor(E),
+    NoFocused,
+    /// Nothing passed the filters, the range is full or history is empty.
+    NoTarget,
+    /// Output does not exist or is the focused one.
+    NoOutput(String),
+    /// Scratchpad window is not running and did not show up after launch.
+    NoWindow,
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(e: E) -> Self {
+        Error::Compositor(e)
+    }
+}
+
+impl<E: Display> Display for Error<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Compositor(e) => write!(f, "{e}"),
+            Error::NoFocused => write!(f, "No focused workspace"),
+            Error::NoTarget => write!(f, "No workspace to go to"),
+            Error::NoOutput(output) => write!(f, "No output {output} to move workspace to"),
+            Error::NoWindow => write!(f, "No scratchpad window"),
+        }
+    }
+}
+
+/// What a window manager has to provide to get its workspaces managed.
+pub trait Compositor {
+    type Error;
+
+    /// All workspaces in the order they are cycled through.
+    fn workspaces(&mut self) -> Result<Vec<Workspace>, Self::Error>;
+    fn focus(&mut self, target: &Target) -> Result<(), Self::Error>;
+    /// Moves the focused window without following it.
+    fn move_window(&mut self, target: &Target) -> Result<(), Self::Error>;
+    fn rename(&mut self, ws: &Workspace, name: &str) -> Result<(), Self::Error>;
+    /// Active outputs ordered left to right.
+    fn outputs(&mut self) -> Result<Vec<String>, Self::Error>;
+    fn move_workspace(&mut self, ws: &Workspace, output: &str) -> Result<(), Self::Error>;
+    /// Yields every time another workspace gets focused, on any output.
+    fn focus_events(&mut self) -> Result<FocusEvents<Self::Error>, Self::Error>;
+
+    /// Workspace `--new` goes to, by default the first free number in range,
+    /// created when focused.
+    fn new_workspace(&mut self, workspaces: Vec<Workspace>, range: (i32, i32)) -> Option<Target> {
+        new_workspace_num(&workspaces, range).map(Target::New)
+    }
+}
+
+pub type FocusEvents<E> = Box<dyn Iterator<Item = Result<(), E>>>;
+
+/// Which workspaces `--next` / `--prev` can land on.
+#[derive(Default)]
+pub struct Filter {
+    pub same_output: bool,
+    pub non_empty: bool,
+    pub numbered: bool,
+    pub named: bool,
+    pub skip: Option<Regex>,
+    pub no_wrap: bool,
+}
+
+impl Filter {
+    fn allows(&self, ws: &Workspace, focused: &Workspace) -> bool {
+        !(self.same_output && ws.output != focused.output
+            || self.non_empty && ws.empty
+            || self.numbered && ws.num.is_none()
+            || self.named && ws.num.is_some()
+            || self
+                .skip
+                .as_ref()
+                .is_some_and(|skip| skip.is_match(&ws.name)))
+    }
+}
+
+pub enum Mode {
+    Switch,
+    /// Create a workspace, numbered from given index unless the output has a range.
+    New(i32),
+    Renumber,
+    /// Move focused workspace to `next`, `prev` or named output.
+    MoveToOutput(String),
+    /// Swap visible workspaces of the focused output and given one.
+    SwapOutputs(String),
+    /// Go to the previously focused workspace from the history.
+    Back,
+    /// Keep the history up to date.
+    Watch,
+}
+
+/// Everything given on the command line.
+pub struct Options {
+    pub mode: Mode,
+    pub direction: Direction,
+    pub do_move: bool,
+    pub do_send: bool,
+    pub filter: Filter,
+    /// Output name -> first and last workspace number it owns.
+    pub ranges: HashMap<String, (i32, i32)>,
+    /// Print commands instead of running them.
+    pub dry_run: bool,
+    pub json: bool,
+}
+
+impl Options {
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        let mut direction = Direction::Next;
+        if matches.get_flag("prev") {
+            direction = Direction::Prev;
+        };
+
+        let filter = Filter {
+            same_output: matches.get_flag("same-output"),
+            non_empty: matches.get_flag("non-empty"),
+            numbered: matches.get_flag("numbered"),
+            named: matches.get_flag("named"),
+            skip: matches.get_one::<String>("skip").map(|skip| {
+                Regex::new(skip).unwrap_or_else(|e| {
+                    eprintln!("Invalid --skip regex: {e}");
+                    std::process::exit(1);
+                })
+            }),
+            no_wrap: matches.get_flag("no-wrap"),
+        };
+
+        let mode = if let Some(output) = matches.get_one::<String>("move-workspace-to-output") {
+            Mode::MoveToOutput(output.clone())
+        } else if let Some(output) = matches.get_one::<String>("swap-outputs") {
+            Mode::SwapOutputs(output.clone())
+        } else if matches.get_flag("back") {
+            Mode::Back
+        } else if matches.get_flag("watch") {
+            Mode::Watch
+        } else if matches.get_flag("renumber") {
+            Mode::Renumber
+        } else if matches.get_flag("new") {
+            Mode::New(matches.get_one::<i32>("start-idx").copied().unwrap_or(1))
+        } else {
+            Mode::Switch
+        };
+
+        let mut ranges =
+            Config::load(matches.get_one::<String>("config").map(|c| c.as_str())).ranges;
+        if let Some(values) = matches.get_many::<(String, (i32, i32))>("range") {
+            ranges.extend(values.cloned());
+        }
+
+        Options {
+            mode,
+            direction,
+            do_move: matches.get_flag("move"),
+            do_send: matches.get_flag("send"),
+            filter,
+            ranges,
+            dry_run: matches.get_flag("dry-run"),
+            json: matches.get_flag("json"),
+        }
+    }
+}
+
+/// Arguments shared by all the workspace tools.
+pub fn args() -> Vec<Arg> {
+    vec![
+        arg!(--"same-output" "Stay on the same output"),
+        arg!(--"non-empty" "Skip empty workspaces"),
+        arg!(--"numbered" "Only numbered workspaces").conflicts_with("named"),
+        arg!(--"named" "Only workspaces not starting with a number"),
+        arg!(--"skip" <REGEX> "Skip workspaces with matching name"),
+        arg!(--"no-wrap" "Stop at the first / last workspace"),
+        arg!(--"next" "Swich to the next workspace (default)"),
+        arg!(--"prev" "Swich to the prev workspace"),
+        arg!(--"move" "Move currently focused container"),
+        arg!(--"send" "Sends currently focused container"),
+        arg!(--"new" "Create new workspace"),
+        arg!(--"start-idx" <index> "Minimum index to start from when creating new workspace")
+            .value_parser(value_parser!(i32)),
+        arg!(--"renumber" "Close gaps between numbered workspaces on focused output")
+            .conflicts_with("new"),
+        arg!(--"move-workspace-to-output" <OUTPUT> "Move focused workspace to next, prev or named output")
+            .conflicts_with_all(["new", "renumber", "move", "send"]),
+        arg!(--"swap-outputs" [OUTPUT] "Swap visible workspaces with next or named output")
+            .default_missing_value("next")
+            .conflicts_with_all(["new", "renumber", "move", "send", "move-workspace-to-output"]),
+        arg!(--"back" "Go back to the previously focused workspace, see --watch")
+            .conflicts_with_all(["new", "renumber", "move-workspace-to-output", "swap-outputs"]),
+        arg!(--"watch" "Record focused workspaces for --back, keeps running")
+            .conflicts_with_all(["new", "renumber", "move-workspace-to-output", "swap-outputs", "back"]),
+        arg!(--"range" <RANGE> "Numbers owned by output, e.g. DP-1=1-9")
+            .value_parser(config::parse_range)
+            .action(ArgAction::Append),
+        arg!(-c --"config" <FILE> "Config file with output ranges, icons and scratchpads").global(true),
+        arg!(--"dry-run" "Print commands instead of running them").global(true),
+        arg!(--"json" "Print chosen workspace as JSON"),
+    ]
+}
+
+/// Does what options say, returns what was chosen unless there is nothing to
+/// report, like for `--renumber`.
+pub fn run<C: Compositor>(
+    compositor: &mut C,
+    options: &Options,
+) -> Result<Option<Outcome>, Error<C::Error>> {
+    let workspaces = compositor.workspaces()?;
+    let focused_output = workspaces
+        .iter()
+        .find(|ws| ws.focused)
+        .map(|ws| ws.output.clone())
+        .ok_or(Error::NoFocused)?;
+    let range = options.ranges.get(&focused_output).copied();
+
+    let target = match options.mode {
+        Mode::Renumber => {
+            for (ws, name) in renumber(&workspaces, range) {
+                compositor.rename(ws, &name)?;
+            }
+            return Ok(None);
+        }
+        Mode::New(start_idx) => {
+            let range = range.unwrap_or((start_idx, start_idx + 99));
+            compositor.new_workspace(workspaces, range)
+        }
+        Mode::MoveToOutput(ref output) => {
+            return move_to_output(compositor, workspaces, output).map(Some)
+        }
+        Mode::SwapOutputs(ref output) => {
+            return swap_outputs(compositor, workspaces, output).map(Some)
+        }
+        Mode::Back => History::load().previous(workspaces).map(Target::Workspace),
+        Mode::Watch => {
+            watch(compositor, workspaces)?;
+            return Ok(None);
+        }
+        Mode::Switch => {
+            find_workspace(workspaces, &options.direction, &options.filter).map(Target::Workspace)
+        }
+    };
+    let target = target.ok_or(Error::NoTarget)?;
+
+    let window_moved = options.do_move || options.do_send;
+    if window_moved {
+        compositor.move_window(&target)?;
+    }
+    if !options.do_send {
+        compositor.focus(&target)?;
+    }
+
+    let created = matches!(options.mode, Mode::New(_));
+    let (name, num, output) = match target {
+        Target::Workspace(ws) => (ws.name, ws.num, ws.output),
+        Target::New(num) => (num.to_string(), Some(num), focused_output),
+    };
+    Ok(Some(Outcome {
+        name,
+        num,
+        output,
+        created,
+        window_moved,
+        focused: !options.do_send,
+    }))
+}
+
+/// Prints the outcome with `--json`, errors are reported and end the process.
+pub fn report<E: Display>(result: Result<Option<Outcome>, Error<E>>, options: &Options) {
+    match result {
+        Ok(Some(outcome)) if options.json => {
+            println!("{}", serde_json::to_string(&outcome).unwrap())
+        }
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Records the focused workspace on every focus change, returns on error only.
+fn watch<C: Compositor>(compositor: &mut C, workspaces: Vec<Workspace>) -> Result<(), C::Error> {
+    let mut history = History::load();
+    let events = compositor.focus_events()?;
+    record_focused(&mut history, &workspaces);
+    for event in events {
+        event?;
+        record_focused(&mut history, &compositor.workspaces()?);
+    }
+    Ok(())
+}
+
+fn record_focused(history: &mut History, workspaces: &[Workspace]) {
+    if let Some(focused) = workspaces.iter().find(|ws| ws.focused) {
+        if history.record(focused) {
+            history
+                .save()
+                .unwrap_or_else(|e| eprintln!("Can not save workspace history: {e}"));
+        }
+    }
+}
+
+fn move_to_output<C: Compositor>(
+    compositor: &mut C,
+    workspaces: Vec<Workspace>,
+    output: &str,
+) -> Result<Outcome, Error<C::Error>> {
+    let focused = workspaces
+        .into_iter()
+        .find(|ws| ws.focused)
+        .ok_or(Error::NoFocused)?;
+    let outputs = compositor.outputs()?;
+    let output = pick_output(&outputs, &focused.output, output)
+        .ok_or_else(|| Error::NoOutput(output.to_string()))?;
+    compositor.move_workspace(&focused, &output)?;
+    let outcome = Outcome::moved(&focused, output);
+    compositor.focus(&Target::Workspace(focused))?;
+    Ok(outcome)
+}
+
+/// Focus stays with the focused workspace, which ends up on the other output.
+fn swap_outputs<C: Compositor>(
+    compositor: &mut C,
+    workspaces: Vec<Workspace>,
+    output: &str,
+) -> Result<Outcome, Error<C::Error>> {
+    let focused_idx = workspaces
+        .iter()
+        .position(|ws| ws.focused)
+        .ok_or(Error::NoFocused)?;
+    let outputs = compositor.outputs()?;
+    let other = pick_output(&outputs, &workspaces[focused_idx].output, output)
+        .ok_or_else(|| Error::NoOutput(output.to_string()))?;
+
+    let mut workspaces = workspaces;
+    let focused = workspaces.swap_remove(focused_idx);
+    compositor.move_workspace(&focused, &other)?;
+    if let Some(visible) = workspaces
+        .iter()
+        .find(|ws| ws.visible && ws.output == other)
+    {
+        compositor.move_workspace(visible, &focused.output)?;
+    }
+    let outcome = Outcome::moved(&focused, other);
+    compositor.focus(&Target::Workspace(focused))?;
+    Ok(outcome)
+}
+
+/// Output for `next`, `prev` or a name, `None` if it is the current one or
+/// does not exist.
+pub fn pick_output(outputs: &[String], current: &str, target: &str) -> Option<String> {
+    let idx = outputs.iter().position(|output| output == current)?;
+    let len = outputs.len();
+    let output = match target {
+        "next" => &outputs[(idx + 1) % len],
+        "prev" => &outputs[(idx + len - 1) % len],
+        name => outputs.iter().find(|output| *output == name)?,
+    };
+    (output != current).then(|| output.clone())
+}
+
+/// Numbered workspaces first, by number, named ones go last in given order.
+pub fn sort_by_num(workspaces: &mut [Workspace]) {
+    workspaces.sort_by_key(|ws| (ws.num.is_none(), ws.num));
+}
+
+/// Next workspace in `direction` from the focused one that passes the filter.
+pub fn find_workspace(
+    mut workspaces: Vec<Workspace>,
+    direction: &Direction,
+    filter: &Filter,
+) -> Option<Workspace> {
+    let focused_idx = workspaces.iter().position(|ws| ws.focused)?;
+
+    let len = workspaces.len();
+    let found = (1..len)
+        .map_while(|step| {
+            let idx = match direction {
+                Direction::Next => focused_idx + step,
+                Direction::Prev => focused_idx + len - step,
+            };
+            let wrapped = match direction {
+                Direction::Next => idx >= len,
+                Direction::Prev => idx < len,
+            };
+            (!(filter.no_wrap && wrapped)).then_some(idx % len)
+        })
+        .find(|&idx| filter.allows(&workspaces[idx], &workspaces[focused_idx]))?;
+
+    Some(workspaces.swap_remove(found))
+}
+
+/// First number in range not taken by a workspace, labelled ones included.
+pub fn new_workspace_num(workspaces: &[Workspace], (from, to): (i32, i32)) -> Option<i32> {
+    let num_workspaces = workspaces
+        .iter()
+        .filter_map(|ws| ws.num)
+        .collect::<Vec<i32>>();
+
+    (from..=to).find(|num| !num_workspaces.contains(num))
+}
+
+/// New names closing the gaps between numbered workspaces on the focused
+/// output, starting at the range start or the lowest number there. Numbers
+/// used on other outputs are skipped, anything after the number is kept.
+/// Renames are ordered so that no new name is still in use when applied.
+pub fn renumber(workspaces: &[Workspace], range: Option<(i32, i32)>) -> Vec<(&Workspace, String)> {
+    let output = match workspaces.iter().find(|ws| ws.focused) {
+        Some(focused) => &focused.output,
+        None => return vec![],
+    };
+    let (ours, others): (Vec<&Workspace>, Vec<&Workspace>) =
+        workspaces.iter().partition(|ws| ws.output == *output);
+    let taken: Vec<i32> = others.iter().filter_map(|ws| ws.num).collect();
+
+    let mut ours: Vec<&Workspace> = ours.into_iter().filter(|ws| ws.num.is_some()).collect();
+    ours.sort_by_key(|ws| ws.num);
+
+    let mut next = match (range, ours.first()) {
+        (Some((from, _)), _) => from,
+        (None, Some(first)) => first.num.unwrap_or_default(),
+        (None, None) => return vec![],
+    };
+
+    // Order is kept, so numbers moving up can only collide with higher ones
+    // moving up and numbers moving down with lower ones moving down.
+    let (mut up, mut down) = (vec![], vec![]);
+    for ws in ours {
+        while taken.contains(&next) {
+            next += 1;
+        }
+        let num = ws.num.unwrap_or_default();
+        let rest = ws.name.trim_start_matches(|c: char| c.is_ascii_digit());
+        let rename = (ws, format!("{next}{rest}"));
+        match num.cmp(&next) {
+            Ordering::Less => up.push(rename),
+            Ordering::Greater => down.push(rename),
+            Ordering::Equal => (),
+        }
+        next += 1;
+    }
+    up.reverse();
+    up.extend(down);
+    up
+}
+
+/// Workspace name made of its number and the icons of apps inside, each icon
+/// once, e.g. `3: firefox nvim`. Empty workspaces get just the number.
+pub fn label(num: i32, apps: &[&str], config: &Config) -> String {
+    let mut icons: Vec<&str> = vec![];
+    for app in apps {
+        let icon = config.icon(app);
+        if !icons.contains(&icon) {
+            icons.push(icon);
+        }
+    }
+    if icons.is_empty() {
+        num.to_string()
+    } else {
+        format!("{num}: {}", icons.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ws(name: &str, output: &str, focused: bool, empty: bool) -> Workspace {
+        Workspace {
+            id: 0,
+            name: name.to_string(),
+            num: name.split(':').next().and_then(|num| num.parse().ok()),
+            output: output.to_string(),
+            focused,
+            visible: focused,
+            empty,
+        }
+    }
+
+    fn workspaces() -> Vec<Workspace> {
+        let mut workspaces = vec![
+            ws("mail", "DP-1", false, false),
+            ws("3", "DP-1", false, false),
+            ws("1", "DP-1", true, false),
+            ws("2:web", "HDMI-A-1", false, true),
+        ];
+        sort_by_num(&mut workspaces);
+        workspaces
+    }
+
+    fn find(direction: Direction, filter: Filter) -> Option<String> {
+        find_workspace(workspaces(), &direction, &filter).map(|ws| ws.name)
+    }
+
+    #[test]
+    fn test_workspaces_are_sorted_by_num() {
+        assert_eq!(
+            find(Direction::Next, Filter::default()),
+            Some("2:web".into())
+        );
+        assert_eq!(
+            find(Direction::Prev, Filter::default()),
+            Some("mail".into())
+        );
+    }
+
+    #[test]
+    fn test_no_wrap_stops_at_the_edge() {
+        let filter = Filter {
+            no_wrap: true,
+            ..Filter::default()
+        };
+        assert_eq!(find(Direction::Prev, filter), None);
+    }
+
+    #[test]
+    fn test_filters_skip_workspaces() {
+        let filter = Filter {
+            same_output: true,
+            ..Filter::default()
+        };
+        assert_eq!(find(Direction::Next, filter), Some("3".into()));
+
+        let filter = Filter {
+            non_empty: true,
+            numbered: true,
+            ..Filter::default()
+        };
+        assert_eq!(find(Direction::Next, filter), Some("3".into()));
+
+        let filter = Filter {
+            skip: Some(Regex::new("^[23]").unwrap()),
+            ..Filter::default()
+        };
+        assert_eq!(find(Direction::Next, filter), Some("mail".into()));
+
+        let filter = Filter {
+            named: true,
+            ..Filter::default()
+        };
+        assert_eq!(find(Direction::Prev, filter), Some("mail".into()));
+    }
+
+    #[test]
+    fn test_new_workspace_takes_first_free_num() {
+        assert_eq!(new_workspace_num(&workspaces(), (1, 100)), Some(4));
+        assert_eq!(new_workspace_num(&workspaces(), (3, 102)), Some(4));
+        assert_eq!(new_workspace_num(&workspaces(), (3, 3)), None);
+    }
+
+    #[test]
+    fn test_renumber_closes_gaps_on_focused_output() {
+        let mut workspaces = workspaces();
+        workspaces.push(ws("7:chat", "DP-1", false, false));
+        workspaces.push(ws("12", "HDMI-A-1", false, false));
+
+        let renames = |range| {
+            renumber(&workspaces, range)
+                .into_iter()
+                .map(|(ws, name)| (ws.name.clone(), name))
+                .collect::<Vec<(String, String)>>()
+        };
+
+        assert_eq!(
+            renames(None),
+            vec![("7:chat".to_string(), "4:chat".to_string())]
+        );
+        assert_eq!(
+            renames(Some((11, 19))),
+            vec![
+                ("7:chat".to_string(), "14:chat".to_string()),
+                ("3".to_string(), "13".to_string()),
+                ("1".to_string(), "11".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_renumber_shifts_up_from_the_highest() {
+        let workspaces = vec![ws("1", "DP-1", true, false), ws("2", "DP-1", false, false)];
+        let renames = renumber(&workspaces, Some((2, 9)))
+            .into_iter()
+            .map(|(ws, name)| (ws.name.as_str(), name))
+            .collect::<Vec<(&str, String)>>();
+
+        assert_eq!(
+            renames,
+            vec![("2", "3".to_string()), ("1", "2".to_string())]
+        );
+    }
+
+    /// Compositor that only remembers what it was asked to focus.
+    struct Fake(Vec<String>);
+
+    impl Compositor for Fake {
+        type Error = String;
+
+        fn workspaces(&mut self) -> Result<Vec<Workspace>, String> {
+            Ok(workspaces())
+        }
+        fn focus(&mut self, target: &Target) -> Result<(), String> {
+            self.0.push(target.name());
+            Ok(())
+        }
+        fn move_window(&mut self, _: &Target) -> Result<(), String> {
+            Ok(())
+        }
+        fn rename(&mut self, _: &Workspace, _: &str) -> Result<(), String> {
+            Ok(())
+        }
+        fn outputs(&mut self) -> Result<Vec<String>, String> {
+            Ok(vec!["DP-1".into(), "HDMI-A-1".into()])
+        }
+        fn move_workspace(&mut self, _: &Workspace, _: &str) -> Result<(), String> {
+            Ok(())
+        }
+        fn focus_events(&mut self) -> Result<FocusEvents<String>, String> {
+            Ok(Box::new(std::iter::empty()))
+        }
+    }
+
+    fn options(mode: Mode, direction: Direction, filter: Filter) -> Options {
+        Options {
+            mode,
+            direction,
+            do_move: false,
+            do_send: false,
+            filter,
+            ranges: HashMap::new(),
+            dry_run: false,
+            json: false,
+        }
+    }
+
+    #[test]
+    fn test_run_reports_outcome_and_missing_target() {
+        let mut fake = Fake(vec![]);
+        let new = options(Mode::New(1), Direction::Next, Filter::default());
+        let outcome = run(&mut fake, &new).unwrap().unwrap();
+        assert_eq!((outcome.name.as_str(), outcome.created), ("4", true));
+        assert_eq!(outcome.output, "DP-1");
+
+        // 1 is the first workspace, nothing before it without wrapping
+        let filter = Filter {
+            no_wrap: true,
+            ..Filter::default()
+        };
+        let result = run(&mut fake, &options(Mode::Switch, Direction::Prev, filter));
+        assert!(matches!(result, Err(Error::NoTarget)));
+        assert_eq!(fake.0, vec!["4"]);
+    }
+
+    #[test]
+    fn test_pick_output() {
+        let outputs = vec![
+            "DP-1".to_string(),
+            "DP-2".to_string(),
+            "HDMI-A-1".to_string(),
+        ];
+        assert_eq!(pick_output(&outputs, "DP-1", "next"), Some("DP-2".into()));
+        assert_eq!(
+            pick_output(&outputs, "DP-1", "prev"),
+            Some("HDMI-A-1".into())
+        );
+        assert_eq!(
+            pick_output(&outputs, "DP-1", "HDMI-A-1"),
+            Some("HDMI-A-1".into())
+        );
+        assert_eq!(pick_output(&outputs, "DP-1", "DP-1"), None);
+        assert_eq!(pick_output(&outputs, "DP-1", "eDP-1"), None);
+        assert_eq!(pick_output(&outputs[..1], "DP-1", "next"), None);
+    }
+
+    #[test]
+    fn test_label_uses_icons_once() {
+        let mut config = Config::default();
+        config
+            .icons
+            .insert("Firefox".to_string(), "web".to_string());
+
+        assert_eq!(label(3, &[], &config), "3");
+        assert_eq!(
+            label(3, &["firefox", "nvim", "firefox"], &config),
+            "3: web nvim"
+        );
+
+        config.default_icon = "?".to_string();
+        assert_eq!(label(3, &["firefox", "nvim"], &config), "3: web ?");
+    }
+}
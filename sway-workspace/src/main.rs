@@ -1,135 +1,247 @@
 use clap::{arg, Command};
-use swayipc::Connection;
-
-enum Direction {
-    Next,
-    Prev,
-}
+use sway_workspace::config::Config;
+use sway_workspace::scratch::{self, Scratchpad, State, Window};
+use sway_workspace::{Compositor, FocusEvents, Options, Target, Workspace};
+use swayipc::{Connection, EventType, Fallible};
+use swayipc_types::{Event, Node, NodeType, WindowChange, WorkspaceChange};
 
 fn cli() -> Command {
     Command::new("sway-workspace")
         .about("Manage workspaces in sway")
-        .args(vec![
-            arg!(--"same-output" "Stay on the same output"),
-            arg!(--"next" "Swich to the next workspace (default)"),
-            arg!(--"prev" "Swich to the prev workspace"),
-            arg!(--"move" "Move currently focused container"),
-            arg!(--"send" "Sends currently focused container"),
-            arg!(--"new" "Create new workspace"),
-            arg!(--"start-idx" <index> "Minimum index to start from when creating new workspace"),
-        ])
+        .args(sway_workspace::args())
+        .subcommand(
+            Command::new("rename")
+                .about("Label numbered workspaces with icons of their windows")
+                .args(vec![
+                    arg!(-w --"watch" "Keep labels updated as windows come and go"),
+                ]),
+        )
+        .subcommand(scratch::cli())
 }
 
-fn main() -> Result<(), swayipc::Error> {
-    let matches = cli().get_matches();
-    let mut sway = swayipc::Connection::new().expect("Can not connect to sway ipc");
+struct Sway {
+    conn: Connection,
+    dry_run: bool,
+}
 
-    let do_move = matches.get_flag("move");
-    let do_send = matches.get_flag("send");
-    let prev = matches.get_flag("prev");
-    let same_output = matches.get_flag("same-output");
+impl Sway {
+    fn command(&mut self, command: String) -> Fallible<()> {
+        if self.dry_run {
+            println!("{command}");
+            return Ok(());
+        }
+        for outcome in self.conn.run_command(command)? {
+            outcome?;
+        }
+        Ok(())
+    }
+}
 
-    let mut direction = Direction::Next;
-    if prev {
-        direction = Direction::Prev;
-    };
+impl Compositor for Sway {
+    type Error = swayipc::Error;
+
+    fn workspaces(&mut self) -> Result<Vec<Workspace>, swayipc::Error> {
+        let mut workspaces: Vec<Workspace> = self
+            .conn
+            .get_workspaces()?
+            .into_iter()
+            .map(|ws| Workspace {
+                id: ws.id,
+                name: ws.name,
+                num: (ws.num >= 0).then_some(ws.num),
+                output: ws.output,
+                focused: ws.focused,
+                visible: ws.visible,
+                empty: ws.focus.is_empty(),
+            })
+            .collect();
+        sway_workspace::sort_by_num(&mut workspaces);
+        Ok(workspaces)
+    }
 
-    if matches.get_flag("new") {
-        let start_idx = matches
-            .get_one::<String>("start-idx")
-            .map_or(1, |idx| idx.parse().expect("Index has to be number"));
-        create_workspace(&mut sway, start_idx, do_move, do_send)?;
-    } else {
-        switch_workspace(&mut sway, &direction, do_move, do_send, same_output)?;
+    fn focus(&mut self, target: &Target) -> Result<(), swayipc::Error> {
+        self.command(format!(
+            "workspace --no-auto-back-and-forth \"{}\"",
+            target.name()
+        ))
     }
 
-    Ok(())
+    fn move_window(&mut self, target: &Target) -> Result<(), swayipc::Error> {
+        self.command(format!("move container to workspace \"{}\"", target.name()))
+    }
+
+    fn rename(&mut self, ws: &Workspace, name: &str) -> Result<(), swayipc::Error> {
+        self.command(format!("rename workspace \"{}\" to \"{name}\"", ws.name))
+    }
+
+    fn outputs(&mut self) -> Result<Vec<String>, swayipc::Error> {
+        let mut outputs = self.conn.get_outputs()?;
+        outputs.retain(|output| output.active);
+        outputs.sort_by_key(|output| (output.rect.x, output.rect.y));
+        Ok(outputs.into_iter().map(|output| output.name).collect())
+    }
+
+    fn move_workspace(&mut self, ws: &Workspace, output: &str) -> Result<(), swayipc::Error> {
+        self.command(format!(
+            "workspace --no-auto-back-and-forth \"{}\"; move workspace to output \"{output}\"",
+            ws.name
+        ))
+    }
+
+    fn focus_events(&mut self) -> Fallible<FocusEvents<swayipc::Error>> {
+        let events = Connection::new()?.subscribe([EventType::Workspace])?;
+        Ok(Box::new(events.filter_map(|event| match event {
+            Ok(Event::Workspace(ev)) if ev.change == WorkspaceChange::Focus => Some(Ok(())),
+            Ok(_) => None,
+            Err(e) => Some(Err(e)),
+        })))
+    }
 }
 
-fn switch_workspace(
-    sway: &mut Connection,
-    direction: &Direction,
-    do_move: bool,
-    do_send: bool,
-    same_output: bool,
-) -> Result<(), swayipc::Error> {
-    let mut workspaces = sway.get_workspaces().map_or(vec![], |ws| ws);
-
-    //allow for cycle through
-    workspaces.extend(workspaces.clone());
-
-    match direction {
-        Direction::Prev => workspaces.reverse(),
-        Direction::Next => (),
-    };
+impl Scratchpad for Sway {
+    type Error = swayipc::Error;
+
+    fn state(&mut self) -> Fallible<State> {
+        let windows = collect_windows(&self.conn.get_tree()?, "", vec![]);
+        let focused = self
+            .conn
+            .get_workspaces()?
+            .into_iter()
+            .find(|ws| ws.focused)
+            .ok_or_else(|| swayipc::Error::CommandFailed("No focused workspace".into()))?;
+        let rect = self
+            .conn
+            .get_outputs()?
+            .into_iter()
+            .find(|output| output.name == focused.output)
+            .map(|output| output.rect)
+            .unwrap_or(focused.rect);
+        Ok(State {
+            windows,
+            workspace: focused.name,
+            output: scratch::Output {
+                name: focused.output,
+                width: rect.width,
+                height: rect.height,
+            },
+        })
+    }
 
-    let mut focused_output: Option<String> = None;
-    let mut switch_to = false;
+    fn command(&mut self, command: String) -> Fallible<()> {
+        Sway::command(self, command)
+    }
+}
 
-    for ws in workspaces {
-        if same_output
-            && focused_output.is_some()
-            && ws.output != focused_output.clone().expect("No focused output")
-        {
-            continue;
-        }
-        if switch_to {
-            if do_move || do_send {
-                sway.run_command(format!("move container to workspace {}", ws.name))?;
-            }
-            if !do_send {
-                sway.run_command(format!("workspace {}", ws.name))?;
+fn main() -> Fallible<()> {
+    let matches = cli().get_matches();
+    let options = Options::from_matches(&matches);
+    let conn = swayipc::Connection::new().unwrap_or_else(|e| {
+        eprintln!("Can not connect to sway ipc: {e}");
+        std::process::exit(1);
+    });
+    let mut sway = Sway {
+        conn,
+        dry_run: options.dry_run,
+    };
+
+    if let Some(rename) = matches.subcommand_matches("rename") {
+        let config = Config::load(matches.get_one::<String>("config").map(|c| c.as_str()));
+        label_workspaces(&mut sway, &config)?;
+
+        if rename.get_flag("watch") {
+            for event in Connection::new()?.subscribe([EventType::Window])? {
+                if let Event::Window(ev) = event? {
+                    if matches!(
+                        ev.change,
+                        WindowChange::New | WindowChange::Close | WindowChange::Move
+                    ) {
+                        label_workspaces(&mut sway, &config)?;
+                    }
+                }
             }
-            break;
         }
-        if ws.focused {
-            focused_output = Some(ws.output);
-            switch_to = true;
+        return Ok(());
+    }
+
+    if let Some(scratch) = matches.subcommand_matches("scratch") {
+        let config = Config::load(matches.get_one::<String>("config").map(|c| c.as_str()));
+        let name = scratch.get_one::<String>("NAME").unwrap();
+        let scratch = scratch::from_config(&config, name);
+        let result = scratch::run(&mut sway, scratch, options.dry_run).map(|_| None);
+        sway_workspace::report(result, &options);
+        return Ok(());
+    }
+
+    sway_workspace::report(sway_workspace::run(&mut sway, &options), &options);
+    Ok(())
+}
+
+/// Renames numbered workspaces whose windows changed, named ones are left alone.
+fn label_workspaces(sway: &mut Sway, config: &Config) -> Fallible<()> {
+    let tree = sway.conn.get_tree()?;
+    for ws in collect_workspaces(&tree, vec![]) {
+        let (Some(name), Some(num)) = (&ws.name, ws.num.filter(|num| *num >= 0)) else {
             continue;
-        }
-        if ws.focused {
-            if do_move || do_send {
-                sway.run_command(format!("move container to workspace {}", ws.name))?;
-            }
-            if !do_send {
-                sway.run_command(format!("workspace {}", ws.name))?;
-            }
-            break;
+        };
+        let apps = collect_apps(ws, vec![]);
+        let label = sway_workspace::label(num, &apps, config);
+        if *name != label {
+            sway.command(format!("rename workspace \"{name}\" to \"{label}\""))?;
         }
     }
-
     Ok(())
 }
 
-fn create_workspace(
-    sway: &mut Connection,
-    start_idx: i32,
-    do_move: bool,
-    do_send: bool,
-) -> Result<(), swayipc::Error> {
-    let workspaces = sway.get_workspaces().map_or(vec![], |ws| ws);
-    let num_workspaces = workspaces
-        .iter()
-        .filter_map(|ws| {
-            if ws.num.to_string() == ws.name {
-                Some(ws.num)
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<i32>>();
+fn collect_workspaces<'a>(node: &'a Node, mut r: Vec<&'a Node>) -> Vec<&'a Node> {
+    if node.node_type == NodeType::Workspace {
+        r.push(node);
+        return r;
+    }
+    for n in &node.nodes {
+        r = collect_workspaces(n, r);
+    }
+    r
+}
 
-    for new_num in start_idx..100 + start_idx {
-        if !num_workspaces.contains(&new_num) {
-            if do_move || do_send {
-                sway.run_command(format!("move container to workspace {new_num}"))?;
-            }
-            if !do_send {
-                sway.run_command(format!("workspace {new_num}"))?;
-            }
-            break;
-        }
+/// Windows in the node with the workspace they are on, scratchpad included.
+fn collect_windows(node: &Node, workspace: &str, mut r: Vec<Window>) -> Vec<Window> {
+    let workspace = match node.node_type {
+        NodeType::Workspace => node.name.as_deref().unwrap_or_default(),
+        _ => workspace,
+    };
+    if node.pid.is_some() && node.nodes.is_empty() {
+        r.push(Window {
+            id: node.id,
+            app_id: node.app_id.clone(),
+            class: node
+                .window_properties
+                .as_ref()
+                .and_then(|p| p.class.clone()),
+            title: node.name.clone(),
+            workspace: workspace.to_string(),
+            focused: node.focused,
+        });
+    }
+    for n in node.nodes.iter().chain(&node.floating_nodes) {
+        r = collect_windows(n, workspace, r);
     }
+    r
+}
 
-    Ok(())
+/// App id, or class for X11 windows, of every window in the node.
+fn collect_apps<'a>(node: &'a Node, mut r: Vec<&'a str>) -> Vec<&'a str> {
+    let class = node
+        .window_properties
+        .as_ref()
+        .and_then(|p| p.class.as_deref());
+    if let Some(app) = node.app_id.as_deref().or(class) {
+        r.push(app);
+    }
+    for n in &node.nodes {
+        r = collect_apps(n, r);
+    }
+    for n in &node.floating_nodes {
+        r = collect_apps(n, r);
+    }
+    r
 }
@@ -0,0 +1,298 @@
+use crate::config::Config;
+use crate::Error;
+use clap::{arg, Command};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Workspace sway and i3 keep scratchpad windows on.
+pub const SCRATCH_WORKSPACE: &str = "__i3_scratch";
+
+/// How long to wait for a launched window, 5s in total.
+const LAUNCH_POLLS: u32 = 50;
+const LAUNCH_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Scratchpad window described in the `[scratch.<name>]` config table:
+///
+/// ```toml
+/// [scratch.term]
+/// app-id = "^scratch-term$"
+/// command = "wezterm start --class scratch-term"
+/// # percent of the output
+/// size = [80, 60]
+///
+/// [scratch.term.outputs]
+/// "eDP-1" = [95, 90]
+/// ```
+///
+/// `app-id`, `class` and `title` are regexes, all given ones have to match,
+/// at least one is required.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Scratch {
+    pub app_id: Option<Pattern>,
+    pub class: Option<Pattern>,
+    pub title: Option<Pattern>,
+    /// Run when no window matches.
+    pub command: Option<String>,
+    pub size: Option<(u32, u32)>,
+    /// Output name -> size used there instead of `size`.
+    #[serde(default)]
+    pub outputs: HashMap<String, (u32, u32)>,
+}
+
+/// Regex checked when the config is loaded.
+#[derive(Deserialize)]
+#[serde(try_from = "String")]
+pub struct Pattern(Regex);
+
+/// Window as far as scratchpads are concerned.
+pub struct Window {
+    pub id: i64,
+    /// App id on wayland, `None` for X11 windows.
+    pub app_id: Option<String>,
+    pub class: Option<String>,
+    pub title: Option<String>,
+    /// Name of the workspace the window is on.
+    pub workspace: String,
+    pub focused: bool,
+}
+
+/// Output the scratchpad is shown on.
+pub struct Output {
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Everything needed to decide what to do with a scratchpad.
+pub struct State {
+    pub windows: Vec<Window>,
+    /// Focused workspace name.
+    pub workspace: String,
+    /// Output of the focused workspace.
+    pub output: Output,
+}
+
+/// Window manager speaking sway / i3 commands.
+pub trait Scratchpad {
+    type Error;
+
+    fn state(&mut self) -> Result<State, Self::Error>;
+    fn command(&mut self, command: String) -> Result<(), Self::Error>;
+}
+
+/// `scratch` subcommand of the sway and i3 tools.
+pub fn cli() -> Command {
+    Command::new("scratch")
+        .about("Toggle scratchpad window, launching it when not running")
+        .args(vec![
+            arg!(<NAME> "Scratchpad from the [scratch.<NAME>] config table"),
+        ])
+}
+
+/// Scratchpad of given name, missing one or one matching every window is
+/// reported and ends the process.
+pub fn from_config<'a>(config: &'a Config, name: &str) -> &'a Scratch {
+    let scratch = config.scratch.get(name).unwrap_or_else(|| {
+        eprintln!("No scratchpad {name} in config");
+        std::process::exit(1);
+    });
+    if !scratch.has_matcher() {
+        eprintln!("Scratchpad {name} needs app-id, class or title");
+        std::process::exit(1);
+    }
+    scratch
+}
+
+/// Toggles the scratchpad, launches its command and waits for the window
+/// when it is not running.
+pub fn run<S: Scratchpad>(
+    wm: &mut S,
+    scratch: &Scratch,
+    dry_run: bool,
+) -> Result<(), Error<S::Error>> {
+    let state = wm.state()?;
+    if let Some(commands) = scratch.toggle(&state.windows, &state.workspace, &state.output) {
+        for command in commands {
+            wm.command(command)?;
+        }
+        return Ok(());
+    }
+
+    let command = scratch.command.as_ref().ok_or(Error::NoWindow)?;
+    wm.command(format!("exec {command}"))?;
+    if dry_run {
+        return Ok(());
+    }
+    for _ in 0..LAUNCH_POLLS {
+        std::thread::sleep(LAUNCH_POLL_INTERVAL);
+        let state = wm.state()?;
+        if let Some(window) = state.windows.iter().find(|window| scratch.matches(window)) {
+            for command in scratch.show(window, &state.output) {
+                wm.command(command)?;
+            }
+            return Ok(());
+        }
+    }
+    Err(Error::NoWindow)
+}
+
+impl Scratch {
+    /// Without any pattern every window would match.
+    pub fn has_matcher(&self) -> bool {
+        self.app_id.is_some() || self.class.is_some() || self.title.is_some()
+    }
+
+    pub fn matches(&self, window: &Window) -> bool {
+        [
+            (&self.app_id, &window.app_id),
+            (&self.class, &window.class),
+            (&self.title, &window.title),
+        ]
+        .iter()
+        .all(|(pattern, value)| match pattern {
+            Some(Pattern(regex)) => regex.is_match(value.as_deref().unwrap_or_default()),
+            None => true,
+        })
+    }
+
+    /// Commands hiding the window when focused, focusing it when shown on
+    /// the focused workspace and bringing it here otherwise. `None` when no
+    /// window matches and the command should be launched.
+    pub fn toggle(
+        &self,
+        windows: &[Window],
+        workspace: &str,
+        output: &Output,
+    ) -> Option<Vec<String>> {
+        let mut matching = windows.iter().filter(|window| self.matches(window));
+        let window = matching
+            .clone()
+            .find(|window| window.focused)
+            .or(matching.next())?;
+
+        let commands = if window.focused {
+            vec![format!("[con_id={}] move scratchpad", window.id)]
+        } else if window.workspace == workspace {
+            vec![format!("[con_id={}] focus", window.id)]
+        } else {
+            self.show(window, output)
+        };
+        Some(commands)
+    }
+
+    /// Commands showing the window floating in the middle of the output,
+    /// sized as configured.
+    pub fn show(&self, window: &Window, output: &Output) -> Vec<String> {
+        let id = window.id;
+        let mut commands = vec![];
+        if window.workspace != SCRATCH_WORKSPACE {
+            commands.push(format!("[con_id={id}] move scratchpad"));
+        }
+        commands.push(format!("[con_id={id}] scratchpad show"));
+        if let Some((width, height)) = self.outputs.get(&output.name).or(self.size.as_ref()) {
+            let width = output.width * *width as i32 / 100;
+            let height = output.height * *height as i32 / 100;
+            commands.push(format!("[con_id={id}] resize set {width} px {height} px"));
+        }
+        commands.push(format!("[con_id={id}] move position center"));
+        commands
+    }
+}
+
+impl Pattern {
+    pub fn is_match(&self, value: &str) -> bool {
+        self.0.is_match(value)
+    }
+}
+
+impl TryFrom<String> for Pattern {
+    type Error = regex::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Regex::new(&value).map(Pattern)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn window(id: i64, app_id: &str, workspace: &str, focused: bool) -> Window {
+        Window {
+            id,
+            app_id: Some(app_id.to_string()),
+            class: None,
+            title: Some("fish".to_string()),
+            workspace: workspace.to_string(),
+            focused,
+        }
+    }
+
+    fn scratch() -> Scratch {
+        Scratch {
+            app_id: Pattern::try_from("^scratch-term$".to_string()).ok(),
+            size: Some((50, 50)),
+            outputs: HashMap::from([("eDP-1".to_string(), (100, 50))]),
+            ..Scratch::default()
+        }
+    }
+
+    fn output(name: &str) -> Output {
+        Output {
+            name: name.to_string(),
+            width: 2000,
+            height: 1000,
+        }
+    }
+
+    #[test]
+    fn test_toggle_hides_focused_and_focuses_visible() {
+        let windows = vec![
+            window(1, "firefox", "1", false),
+            window(2, "scratch-term", "1", true),
+        ];
+        assert_eq!(
+            scratch().toggle(&windows, "1", &output("DP-1")),
+            Some(vec!["[con_id=2] move scratchpad".to_string()])
+        );
+
+        let windows = vec![
+            window(1, "firefox", "1", true),
+            window(2, "scratch-term", "1", false),
+        ];
+        assert_eq!(
+            scratch().toggle(&windows, "1", &output("DP-1")),
+            Some(vec!["[con_id=2] focus".to_string()])
+        );
+
+        let windows = vec![window(1, "firefox", "1", true)];
+        assert_eq!(scratch().toggle(&windows, "1", &output("DP-1")), None);
+    }
+
+    #[test]
+    fn test_scratch_needs_matcher() {
+        assert!(scratch().has_matcher());
+        assert!(!Scratch::default().has_matcher());
+    }
+
+    #[test]
+    fn test_toggle_shows_sized_per_output() {
+        let windows = vec![window(2, "scratch-term", SCRATCH_WORKSPACE, false)];
+        assert_eq!(
+            scratch().toggle(&windows, "1", &output("eDP-1")),
+            Some(vec![
+                "[con_id=2] scratchpad show".to_string(),
+                "[con_id=2] resize set 2000 px 500 px".to_string(),
+                "[con_id=2] move position center".to_string(),
+            ])
+        );
+
+        let windows = vec![window(2, "scratch-term", "3", false)];
+        let commands = scratch().toggle(&windows, "1", &output("DP-1")).unwrap();
+        assert_eq!(commands[0], "[con_id=2] move scratchpad");
+        assert_eq!(commands[2], "[con_id=2] resize set 1000 px 500 px");
+    }
+}